@@ -0,0 +1,20 @@
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct IndexCreated {
+    pub index: Pubkey,
+    pub owner: Pubkey,
+    pub namespace: Pubkey,
+    pub is_serial: bool,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct PointerCreated {
+    pub index: Pubkey,
+    pub pointer: Pubkey,
+    pub proof: Pubkey,
+    pub name: String,
+    pub value: Pubkey,
+    pub timestamp: u64,
+}
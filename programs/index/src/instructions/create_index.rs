@@ -3,7 +3,7 @@ use {
         prelude::*,
         solana_program::system_program
     },
-    crate::state::*,
+    crate::{events::*, state::*},
     std::mem::size_of
 };
 
@@ -55,6 +55,14 @@ pub fn handler(
     index.count = 0;
     index.is_serial = is_serial;
     index.bump = bump;
+
+    emit!(IndexCreated {
+        index: index.key(),
+        owner: owner.key(),
+        namespace: namespace.key(),
+        is_serial,
+        timestamp: Clock::get()?.unix_timestamp as u64,
+    });
     
-    return Ok(());
+    Ok(())
 }
\ No newline at end of file
@@ -3,7 +3,7 @@ use {
         prelude::*,
         solana_program::system_program
     },
-    crate::state,
+    crate::{events::*, state},
     std::mem::size_of
 };
 
@@ -90,6 +90,15 @@ pub fn handler(
 
     // Increment index counter.
     index.count += 1;
+
+    emit!(PointerCreated {
+        index: index.key(),
+        pointer: pointer.key(),
+        proof: proof.key(),
+        name,
+        value,
+        timestamp: Clock::get()?.unix_timestamp as u64,
+    });
     
-    return Ok(());
+    Ok(())
 }
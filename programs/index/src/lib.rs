@@ -1,3 +1,4 @@
+pub mod events;
 mod instructions;
 pub mod state;
 
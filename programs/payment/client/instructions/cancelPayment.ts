@@ -0,0 +1,45 @@
+import { PublicKey, TransactionInstruction } from "@solana/web3.js";
+import { TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { program } from "..";
+import { findVaultPDA } from "../pda";
+
+export type CancelPaymentProps = {
+  payment: PublicKey;
+  signer: PublicKey;
+  // The payment's pending tasks, which are closed to refund their rent to whoever paid it.
+  tasks: PublicKey[];
+};
+
+export async function cancelPayment({
+  payment,
+  signer,
+  tasks,
+}: CancelPaymentProps): Promise<TransactionInstruction> {
+  const paymentData = await program.account.payment.fetch(payment);
+  const vaultPDA = await findVaultPDA(
+    paymentData.debtor,
+    paymentData.debtorTokens
+  );
+
+  // Each task is passed with its rent payer after it.
+  const remainingAccounts = [];
+  for (const task of tasks) {
+    const taskData = await program.account.task.fetch(task);
+    remainingAccounts.push(
+      { pubkey: task, isSigner: false, isWritable: true },
+      { pubkey: taskData.payer, isSigner: false, isWritable: true }
+    );
+  }
+
+  return program.instruction.cancelPayment({
+    accounts: {
+      debtor: paymentData.debtor,
+      debtorTokens: paymentData.debtorTokens,
+      payment: payment,
+      signer: signer,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      vault: vaultPDA.address,
+    },
+    remainingAccounts,
+  });
+}
@@ -0,0 +1,184 @@
+import { BN } from "@project-serum/anchor";
+import {
+  PublicKey,
+  SystemProgram,
+  SYSVAR_CLOCK_PUBKEY,
+  TransactionInstruction,
+} from "@solana/web3.js";
+import { TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { indexProgram, program } from "..";
+import {
+  authorityPDA,
+  configPDA,
+  findPaymentIndexPDAs,
+  findPointerPDAs,
+  findTaskIndexPDAs,
+  findVaultPDA,
+  SEED_PAYMENT,
+  SEED_TASK,
+} from "../pda";
+import { findPDA, PDA } from "../../../../utils";
+
+export type CreatePaymentProps = {
+  creditor: PublicKey;
+  creditorTokens: PublicKey;
+  debtor: PublicKey;
+  debtorTokens: PublicKey;
+  mint: PublicKey;
+  memo: string;
+  amount: number;
+  recurrenceInterval: number;
+  startAt: number;
+  endAt: number;
+  gracePeriod: number;
+  retryInterval: number;
+  maxRetries: number;
+  maxFailures: number;
+};
+
+export type CreatePaymentResponse = {
+  ix: TransactionInstruction;
+  paymentPDA: PDA;
+  taskPDA: PDA;
+};
+
+export async function createPayment({
+  creditor,
+  creditorTokens,
+  debtor,
+  debtorTokens,
+  mint,
+  memo,
+  amount,
+  recurrenceInterval,
+  startAt,
+  endAt,
+  gracePeriod,
+  retryInterval,
+  maxRetries,
+  maxFailures,
+}: CreatePaymentProps): Promise<CreatePaymentResponse> {
+  // Find the payment at the next position in the debtor's payment index.
+  const debtorPaymentIndexPDAs = await findPaymentIndexPDAs(
+    debtor,
+    "debtor",
+    indexProgram.programId
+  );
+  const debtorPaymentIndexPDA = debtorPaymentIndexPDAs.indexPDA;
+  const debtorPaymentIndexData = await indexProgram.account.index.fetch(
+    debtorPaymentIndexPDA.address
+  );
+  const paymentPDA = await findPDA(
+    [
+      SEED_PAYMENT,
+      debtorPaymentIndexPDA.address.toBuffer(),
+      Buffer.from(debtorPaymentIndexData.count.toString()),
+    ],
+    program.programId
+  );
+  const debtorPaymentPointers = await findPointerPDAs(
+    debtorPaymentIndexPDA.address,
+    debtorPaymentIndexData.count.toNumber(),
+    paymentPDA.address,
+    indexProgram.programId
+  );
+
+  // Find the payment's pointer in the creditor's payment index.
+  const creditorPaymentIndexPDAs = await findPaymentIndexPDAs(
+    creditor,
+    "creditor",
+    indexProgram.programId
+  );
+  const creditorPaymentIndexPDA = creditorPaymentIndexPDAs.indexPDA;
+  const creditorPaymentIndexData = await indexProgram.account.index.fetch(
+    creditorPaymentIndexPDA.address
+  );
+  const creditorPaymentPointers = await findPointerPDAs(
+    creditorPaymentIndexPDA.address,
+    creditorPaymentIndexData.count.toNumber(),
+    paymentPDA.address,
+    indexProgram.programId
+  );
+
+  // Find the first task at the next position in the task index of the payment's start.
+  const taskIndexPDAs = await findTaskIndexPDAs(
+    startAt,
+    indexProgram.programId
+  );
+  const taskIndexData = await indexProgram.account.index.fetch(
+    taskIndexPDAs.indexPDA.address
+  );
+  const taskPDA = await findPDA(
+    [
+      SEED_TASK,
+      taskIndexPDAs.indexPDA.address.toBuffer(),
+      Buffer.from(taskIndexData.count.toString()),
+    ],
+    program.programId
+  );
+  const taskPointers = await findPointerPDAs(
+    taskIndexPDAs.indexPDA.address,
+    taskIndexData.count.toNumber(),
+    taskPDA.address,
+    indexProgram.programId
+  );
+
+  const vaultPDA = await findVaultPDA(debtor, debtorTokens);
+
+  const ix = program.instruction.createPayment(
+    memo,
+    new BN(amount),
+    null,
+    new BN(recurrenceInterval),
+    null,
+    new BN(startAt),
+    new BN(endAt),
+    new BN(gracePeriod),
+    new BN(retryInterval),
+    maxRetries,
+    maxFailures,
+    null,
+    { scheduled: {} },
+    new BN(0),
+    creditorPaymentPointers.pointerPDA.bump,
+    creditorPaymentPointers.proofPDA.bump,
+    debtorPaymentPointers.pointerPDA.bump,
+    debtorPaymentPointers.proofPDA.bump,
+    paymentPDA.bump,
+    taskPDA.bump,
+    taskPointers.pointerPDA.bump,
+    taskPointers.proofPDA.bump,
+    {
+      accounts: {
+        authority: authorityPDA.address,
+        clock: SYSVAR_CLOCK_PUBKEY,
+        config: configPDA.address,
+        creditor: creditor,
+        creditorPaymentIndex: creditorPaymentIndexPDA.address,
+        creditorPaymentNamespace: creditorPaymentIndexPDAs.namespacePDA.address,
+        creditorPaymentPointer: creditorPaymentPointers.pointerPDA.address,
+        creditorPaymentProof: creditorPaymentPointers.proofPDA.address,
+        creditorTokens: creditorTokens,
+        debtor: debtor,
+        debtorPaymentIndex: debtorPaymentIndexPDA.address,
+        debtorPaymentNamespace: debtorPaymentIndexPDAs.namespacePDA.address,
+        debtorPaymentPointer: debtorPaymentPointers.pointerPDA.address,
+        debtorPaymentProof: debtorPaymentPointers.proofPDA.address,
+        debtorTokens: debtorTokens,
+        indexProgram: indexProgram.programId,
+        mint: mint,
+        payment: paymentPDA.address,
+        systemProgram: SystemProgram.programId,
+        task: taskPDA.address,
+        taskIndex: taskIndexPDAs.indexPDA.address,
+        taskNamespace: taskIndexPDAs.namespacePDA.address,
+        taskPointer: taskPointers.pointerPDA.address,
+        taskProof: taskPointers.proofPDA.address,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        vault: vaultPDA.address,
+      },
+    }
+  );
+
+  return { ix, paymentPDA, taskPDA };
+}
@@ -3,13 +3,21 @@ import {
   SystemProgram,
   TransactionInstruction,
 } from "@solana/web3.js";
-import { authorityPDA, program, SEED_INDEX, SEED_NAMESPACE } from "..";
+import {
+  authorityPDA,
+  program,
+  SEED_INDEX,
+  SEED_NAMESPACE,
+  SEED_ROLE_CREDITOR,
+  SEED_ROLE_DEBTOR,
+} from "..";
 import { findPDA, PDA } from "../../../../utils";
 
 export type CreatePaymentIndexProps = {
   indexProgram: PublicKey;
   party: PublicKey;
   payer: PublicKey;
+  role: "creditor" | "debtor";
 };
 
 export type CreatePaymentIndexResponse = {
@@ -22,9 +30,14 @@ export async function createPaymentIndex({
   indexProgram,
   party,
   payer,
+  role,
 }: CreatePaymentIndexProps): Promise<CreatePaymentIndexResponse> {
   const namespacePDA = await findPDA(
-    [SEED_NAMESPACE, party.toBuffer()],
+    [
+      SEED_NAMESPACE,
+      party.toBuffer(),
+      role === "creditor" ? SEED_ROLE_CREDITOR : SEED_ROLE_DEBTOR,
+    ],
     program.programId
   );
 
@@ -38,6 +51,7 @@ export async function createPaymentIndex({
   );
 
   const ix = program.instruction.createPaymentIndex(
+    { [role]: {} },
     indexPDA.bump,
     namespacePDA.bump,
     {
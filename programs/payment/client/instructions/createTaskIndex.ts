@@ -0,0 +1,53 @@
+import { BN } from "@project-serum/anchor";
+import {
+  PublicKey,
+  SystemProgram,
+  SYSVAR_CLOCK_PUBKEY,
+  TransactionInstruction,
+} from "@solana/web3.js";
+import { program } from "..";
+import { authorityPDA, configPDA, findTaskIndexPDAs } from "../pda";
+import { PDA } from "../../../../utils";
+
+export type CreateTaskIndexProps = {
+  indexProgram: PublicKey;
+  processAt: number;
+  signer: PublicKey;
+};
+
+export type CreateTaskIndexResponse = {
+  ix: TransactionInstruction;
+  indexPDA: PDA;
+  namespacePDA: PDA;
+};
+
+export async function createTaskIndex({
+  indexProgram,
+  processAt,
+  signer,
+}: CreateTaskIndexProps): Promise<CreateTaskIndexResponse> {
+  const { indexPDA, namespacePDA } = await findTaskIndexPDAs(
+    processAt,
+    indexProgram
+  );
+
+  const ix = program.instruction.createTaskIndex(
+    new BN(processAt),
+    indexPDA.bump,
+    namespacePDA.bump,
+    {
+      accounts: {
+        authority: authorityPDA.address,
+        clock: SYSVAR_CLOCK_PUBKEY,
+        config: configPDA.address,
+        index: indexPDA.address,
+        indexProgram: indexProgram,
+        namespace: namespacePDA.address,
+        signer: signer,
+        systemProgram: SystemProgram.programId,
+      },
+    }
+  );
+
+  return { ix, indexPDA, namespacePDA };
+}
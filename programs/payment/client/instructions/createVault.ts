@@ -0,0 +1,36 @@
+import {
+  PublicKey,
+  SystemProgram,
+  TransactionInstruction,
+} from "@solana/web3.js";
+import { program } from "..";
+import { findVaultPDA } from "../pda";
+import { PDA } from "../../../../utils";
+
+export type CreateVaultProps = {
+  debtor: PublicKey;
+  debtorTokens: PublicKey;
+};
+
+export type CreateVaultResponse = {
+  ix: TransactionInstruction;
+  vaultPDA: PDA;
+};
+
+export async function createVault({
+  debtor,
+  debtorTokens,
+}: CreateVaultProps): Promise<CreateVaultResponse> {
+  const vaultPDA = await findVaultPDA(debtor, debtorTokens);
+
+  const ix = program.instruction.createVault(vaultPDA.bump, {
+    accounts: {
+      debtor: debtor,
+      debtorTokens: debtorTokens,
+      systemProgram: SystemProgram.programId,
+      vault: vaultPDA.address,
+    },
+  });
+
+  return { ix, vaultPDA };
+}
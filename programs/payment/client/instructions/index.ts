@@ -1,2 +1,8 @@
+export * from "./cancelPayment";
+export * from "./createPayment";
 export * from "./createPaymentIndex";
+export * from "./createTaskIndex";
+export * from "./createVault";
 export * from "./initializeProgram";
+export * from "./processTask";
+export * from "./updateConfig";
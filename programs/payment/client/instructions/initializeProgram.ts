@@ -5,7 +5,12 @@ import {
   TransactionInstruction,
 } from "@solana/web3.js";
 import { program } from "..";
-import { authorityPDA, configPDA, treasuryPDA } from "../pda";
+import {
+  authorityPDA,
+  configPDA,
+  programDataPDA,
+  treasuryPDA,
+} from "../pda";
 
 export type InitializeProgramProps = {
   signer: PublicKey;
@@ -28,6 +33,7 @@ export function initializeProgram({
       accounts: {
         authority: authorityPDA.address,
         config: configPDA.address,
+        programData: programDataPDA.address,
         signer: signer,
         systemProgram: SystemProgram.programId,
         treasury: treasuryPDA.address,
@@ -0,0 +1,133 @@
+import {
+  Keypair,
+  PublicKey,
+  SystemProgram,
+  SYSVAR_CLOCK_PUBKEY,
+  TransactionInstruction,
+} from "@solana/web3.js";
+import { TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { indexProgram, program } from "..";
+import {
+  authorityPDA,
+  findPaymentIndexPDAs,
+  findPointerPDAs,
+  findTaskIndexPDAs,
+  findVaultPDA,
+  SEED_TASK,
+  treasuryPDA,
+} from "../pda";
+import { findPDA, PDA } from "../../../../utils";
+
+export type ProcessTaskProps = {
+  task: PublicKey;
+  signer: PublicKey;
+  treasuryTokens: PublicKey;
+  // The process time of the next task, if the payment schedules one.
+  nextProcessAt?: number;
+};
+
+export type ProcessTaskResponse = {
+  ix: TransactionInstruction;
+  nextTaskPDA?: PDA;
+};
+
+export async function processTask({
+  task,
+  signer,
+  treasuryTokens,
+  nextProcessAt,
+}: ProcessTaskProps): Promise<ProcessTaskResponse> {
+  const taskData = await program.account.task.fetch(task);
+  const paymentData = await program.account.payment.fetch(taskData.payment);
+  const taskIndexPDAs = await findTaskIndexPDAs(
+    taskData.processAt.toNumber(),
+    indexProgram.programId
+  );
+  const debtorPaymentIndexPDAs = await findPaymentIndexPDAs(
+    paymentData.debtor,
+    "debtor",
+    indexProgram.programId
+  );
+  const vaultPDA = await findVaultPDA(
+    paymentData.debtor,
+    paymentData.debtorTokens
+  );
+
+  // Find the next task at the next position in the task index of its process time. A payment
+  // that schedules no next task leaves these accounts unused.
+  let nextTaskPDA: PDA | undefined;
+  let nextTaskAccounts = {
+    nextTask: Keypair.generate().publicKey,
+    nextTaskIndex: Keypair.generate().publicKey,
+    nextTaskNamespace: Keypair.generate().publicKey,
+    nextTaskPointer: Keypair.generate().publicKey,
+    nextTaskProof: Keypair.generate().publicKey,
+  };
+  let nextTaskBumps = { task: 0, pointer: 0, proof: 0 };
+  if (nextProcessAt !== undefined) {
+    const nextTaskIndexPDAs = await findTaskIndexPDAs(
+      nextProcessAt,
+      indexProgram.programId
+    );
+    const nextTaskIndexData = await indexProgram.account.index.fetch(
+      nextTaskIndexPDAs.indexPDA.address
+    );
+    nextTaskPDA = await findPDA(
+      [
+        SEED_TASK,
+        nextTaskIndexPDAs.indexPDA.address.toBuffer(),
+        Buffer.from(nextTaskIndexData.count.toString()),
+      ],
+      program.programId
+    );
+    const nextTaskPointers = await findPointerPDAs(
+      nextTaskIndexPDAs.indexPDA.address,
+      nextTaskIndexData.count.toNumber(),
+      nextTaskPDA.address,
+      indexProgram.programId
+    );
+    nextTaskAccounts = {
+      nextTask: nextTaskPDA.address,
+      nextTaskIndex: nextTaskIndexPDAs.indexPDA.address,
+      nextTaskNamespace: nextTaskIndexPDAs.namespacePDA.address,
+      nextTaskPointer: nextTaskPointers.pointerPDA.address,
+      nextTaskProof: nextTaskPointers.proofPDA.address,
+    };
+    nextTaskBumps = {
+      task: nextTaskPDA.bump,
+      pointer: nextTaskPointers.pointerPDA.bump,
+      proof: nextTaskPointers.proofPDA.bump,
+    };
+  }
+
+  const ix = program.instruction.processTask(
+    nextTaskBumps.task,
+    nextTaskBumps.pointer,
+    nextTaskBumps.proof,
+    {
+      accounts: {
+        authority: authorityPDA.address,
+        clock: SYSVAR_CLOCK_PUBKEY,
+        creditor: paymentData.creditor,
+        creditorTokens: paymentData.creditorTokens,
+        debtor: paymentData.debtor,
+        debtorPaymentIndex: debtorPaymentIndexPDAs.indexPDA.address,
+        debtorTokens: paymentData.debtorTokens,
+        indexProgram: indexProgram.programId,
+        ...nextTaskAccounts,
+        payment: taskData.payment,
+        signer: signer,
+        systemProgram: SystemProgram.programId,
+        task: task,
+        taskIndex: taskIndexPDAs.indexPDA.address,
+        taskNamespace: taskIndexPDAs.namespacePDA.address,
+        treasury: treasuryPDA.address,
+        treasuryTokens: treasuryTokens,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        vault: vaultPDA.address,
+      },
+    }
+  );
+
+  return { ix, nextTaskPDA };
+}
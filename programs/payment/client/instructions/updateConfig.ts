@@ -0,0 +1,39 @@
+import { BN } from "@project-serum/anchor";
+import { PublicKey, TransactionInstruction } from "@solana/web3.js";
+import { program } from "..";
+import { configPDA } from "../pda";
+
+export type UpdateConfigProps = {
+  admin: PublicKey;
+  transferFeeDistributor: number;
+  transferFeeProgram: number;
+  programFeeBps: number;
+  programFeeMin: number;
+  programFeeMax: number;
+  timeGranularity: number;
+};
+
+export function updateConfig({
+  admin,
+  transferFeeDistributor,
+  transferFeeProgram,
+  programFeeBps,
+  programFeeMin,
+  programFeeMax,
+  timeGranularity,
+}: UpdateConfigProps): TransactionInstruction {
+  return program.instruction.updateConfig(
+    new BN(transferFeeDistributor),
+    new BN(transferFeeProgram),
+    programFeeBps,
+    new BN(programFeeMin),
+    new BN(programFeeMax),
+    new BN(timeGranularity),
+    {
+      accounts: {
+        admin: admin,
+        config: configPDA.address,
+      },
+    }
+  );
+}
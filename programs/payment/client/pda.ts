@@ -1,8 +1,17 @@
 import { PublicKey } from "@solana/web3.js";
 import { findPDA, PDA } from "../../../utils";
+import { program } from "./program";
 
 // PDAs
-export let authorityPDA: PDA, configPDA: PDA, treasuryPDA: PDA;
+export let authorityPDA: PDA,
+  configPDA: PDA,
+  programDataPDA: PDA,
+  treasuryPDA: PDA;
+
+// Programs
+export const BPF_LOADER_UPGRADEABLE_ID = new PublicKey(
+  "BPFLoaderUpgradeab1e11111111111111111111111"
+);
 
 // SEEDS
 export const SEED_AUTHORITY = Buffer.from("aut");
@@ -10,10 +19,96 @@ export const SEED_CONFIG = Buffer.from("cfg");
 export const SEED_TREASURY = Buffer.from("trs");
 export const SEED_INDEX = Buffer.from("idx");
 export const SEED_NAMESPACE = Buffer.from("ns");
+export const SEED_ROLE_CREDITOR = Buffer.from("cr");
+export const SEED_ROLE_DEBTOR = Buffer.from("db");
+export const SEED_PAYMENT = Buffer.from("pay");
+export const SEED_POINTER = Buffer.from("ptr");
+export const SEED_PROOF = Buffer.from("prf");
+export const SEED_TASK = Buffer.from("tsk");
+export const SEED_TASK_NAMESPACE = Buffer.from("tsk_ns");
+export const SEED_VAULT = Buffer.from("vlt");
 
 // loadPDAs ...
 export async function loadPDAs(programId: PublicKey) {
   authorityPDA = await findPDA([SEED_AUTHORITY], programId);
   configPDA = await findPDA([SEED_CONFIG], programId);
+  programDataPDA = await findPDA(
+    [programId.toBuffer()],
+    BPF_LOADER_UPGRADEABLE_ID
+  );
   treasuryPDA = await findPDA([SEED_TREASURY], programId);
 }
+
+// findPaymentIndexPDAs ...
+export async function findPaymentIndexPDAs(
+  party: PublicKey,
+  role: "creditor" | "debtor",
+  indexProgram: PublicKey
+): Promise<{ indexPDA: PDA; namespacePDA: PDA }> {
+  const namespacePDA = await findPDA(
+    [
+      SEED_NAMESPACE,
+      party.toBuffer(),
+      role === "creditor" ? SEED_ROLE_CREDITOR : SEED_ROLE_DEBTOR,
+    ],
+    program.programId
+  );
+  const indexPDA = await findPDA(
+    [
+      SEED_INDEX,
+      authorityPDA.address.toBuffer(),
+      namespacePDA.address.toBuffer(),
+    ],
+    indexProgram
+  );
+  return { indexPDA, namespacePDA };
+}
+
+// findTaskIndexPDAs ...
+export async function findTaskIndexPDAs(
+  processAt: number,
+  indexProgram: PublicKey
+): Promise<{ indexPDA: PDA; namespacePDA: PDA }> {
+  const namespacePDA = await findPDA(
+    [SEED_TASK_NAMESPACE, Buffer.from(`${processAt}`)],
+    program.programId
+  );
+  const indexPDA = await findPDA(
+    [
+      SEED_INDEX,
+      authorityPDA.address.toBuffer(),
+      namespacePDA.address.toBuffer(),
+    ],
+    indexProgram
+  );
+  return { indexPDA, namespacePDA };
+}
+
+// findPointerPDAs finds the pointer and proof that index an account at the given position.
+export async function findPointerPDAs(
+  index: PublicKey,
+  count: number,
+  value: PublicKey,
+  indexProgram: PublicKey
+): Promise<{ pointerPDA: PDA; proofPDA: PDA }> {
+  const pointerPDA = await findPDA(
+    [SEED_POINTER, index.toBuffer(), Buffer.from(`${count}`)],
+    indexProgram
+  );
+  const proofPDA = await findPDA(
+    [SEED_PROOF, index.toBuffer(), value.toBuffer()],
+    indexProgram
+  );
+  return { pointerPDA, proofPDA };
+}
+
+// findVaultPDA ...
+export async function findVaultPDA(
+  debtor: PublicKey,
+  debtorTokens: PublicKey
+): Promise<PDA> {
+  return findPDA(
+    [SEED_VAULT, debtor.toBuffer(), debtorTokens.toBuffer()],
+    program.programId
+  );
+}
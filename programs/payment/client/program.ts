@@ -1,6 +1,7 @@
 import * as anchor from "@project-serum/anchor";
 import { Program } from "@project-serum/anchor";
 import { Connection } from "@solana/web3.js";
+import { IndexProgram } from "../../../target/types/index_program";
 import { PaymentProgram } from "../../../target/types/payment_program";
 
 export const program = (anchor as any).workspace
@@ -9,3 +10,6 @@ export const program = (anchor as any).workspace
 export function connection(): Connection {
   return program.provider.connection;
 }
+
+export const indexProgram = (anchor as any).workspace
+  .IndexProgram as Program<IndexProgram>;
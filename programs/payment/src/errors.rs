@@ -1,4 +1,4 @@
-use anchor_lang::prelude::*;
+use {crate::schedule::ScheduleError, anchor_lang::prelude::*};
 
 #[error]
 pub enum ErrorCode {
@@ -6,8 +6,89 @@ pub enum ErrorCode {
     InsufficientBalance,
     #[msg("The timestamps must be chronological.")]
     InvalidChronology,
-    #[msg("Task sets cannot be scheduled for processing intra-minute.")]
-    InvalidProcessAtIntraMinute,
+    #[msg("Task sets must be scheduled at the start of a time bucket.")]
+    InvalidProcessAtGranularity,
     #[msg("Task sets cannot be scheduled for processing in the past.")]
     InvalidProcessAtPast,
+    #[msg("Tasks cannot be processed before their process_at time.")]
+    InvalidProcessAtFuture,
+    #[msg("The task index does not match the task's process_at time.")]
+    InvalidTaskIndex,
+    #[msg("Only pending tasks can be processed.")]
+    InvalidTaskStatus,
+    #[msg("The task does not belong to this payment.")]
+    InvalidTask,
+    #[msg("The payment is not active.")]
+    InvalidPaymentStatus,
+    #[msg("The signer is not a party to this payment.")]
+    InvalidSigner,
+    #[msg("The creditor must sign changes that reduce what they are paid.")]
+    CreditorSignatureRequired,
+    #[msg("Only recurring payments can be paused.")]
+    InvalidPauseOneTime,
+    #[msg("Retries must be scheduled in units of the time granularity, within the grace period.")]
+    InvalidRetryInterval,
+    #[msg("The signer is not the program admin.")]
+    InvalidAdmin,
+    #[msg("The signer is not the pending program admin.")]
+    InvalidPendingAdmin,
+    #[msg("The treasury cannot withdraw below its rent-exempt minimum.")]
+    InsufficientTreasuryBalance,
+    #[msg("Fees cannot exceed 10000 basis points and the minimum fee cannot exceed the maximum.")]
+    InvalidFee,
+    #[msg("The treasury token account must belong to the treasury and match the mint.")]
+    InvalidTreasuryTokens,
+    #[msg("Recurrence intervals must be in units of the time granularity.")]
+    InvalidRecurrenceInterval,
+    #[msg("The time granularity must be greater than zero.")]
+    InvalidTimeGranularity,
+    #[msg("The recurrence rule is invalid or does not match the schedule's start.")]
+    InvalidRecurrenceRule,
+    #[msg("The streaming rate must be greater than zero.")]
+    InvalidRate,
+    #[msg("The payment's mode does not support this instruction.")]
+    InvalidPaymentMode,
+    #[msg("The stream has not accrued any tokens since it was last claimed.")]
+    NothingToClaim,
+    #[msg("The debtor's token balance or allowance does not cover the claim.")]
+    InsufficientTokens,
+    #[msg("The amount rule is invalid.")]
+    InvalidAmountRule,
+    #[msg("The invoice has already been accepted.")]
+    InvalidInvoiceStatus,
+    #[msg("The invoice has expired.")]
+    InvoiceExpired,
+    #[msg("Open invoices cannot be closed until they expire.")]
+    InvoiceNotExpired,
+    #[msg("Split shares must be nonzero and add up to at most 10000 basis points.")]
+    InvalidSplits,
+    #[msg("The split recipient token accounts must match the payment's split table and mint.")]
+    InvalidSplitTokens,
+    #[msg("Allowances can only be charged between their start and end.")]
+    InvalidChargeTime,
+    #[msg("The charge exceeds what remains of the allowance in the current period.")]
+    AllowanceExceeded,
+    #[msg("Usage reports must be for at least one unit.")]
+    InvalidUsage,
+    #[msg("The transfer fees overflow.")]
+    FeeOverflow,
+    #[msg("Metered payments must have a unit price, and scheduled payments cannot.")]
+    InvalidUnitPrice,
+}
+
+impl From<ScheduleError> for ProgramError {
+    fn from(err: ScheduleError) -> Self {
+        match err {
+            ScheduleError::InvalidChronology => ErrorCode::InvalidChronology,
+            ScheduleError::InvalidTimeGranularity => ErrorCode::InvalidTimeGranularity,
+            ScheduleError::InvalidStartAt => ErrorCode::InvalidProcessAtGranularity,
+            ScheduleError::InvalidRecurrenceInterval => ErrorCode::InvalidRecurrenceInterval,
+            ScheduleError::InvalidFee => ErrorCode::InvalidFee,
+            ScheduleError::InvalidRecurrenceRule => ErrorCode::InvalidRecurrenceRule,
+            ScheduleError::InvalidAmountRule => ErrorCode::InvalidAmountRule,
+            ScheduleError::InvalidSplits => ErrorCode::InvalidSplits,
+            ScheduleError::Overflow => ErrorCode::FeeOverflow,
+        }
+        .into()
+    }
 }
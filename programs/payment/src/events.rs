@@ -0,0 +1,273 @@
+use {
+    crate::{
+        schedule::{AmountRule, RecurrenceRule},
+        state::*,
+    },
+    anchor_lang::prelude::*,
+};
+
+#[event]
+pub struct PaymentCreated {
+    pub payment: Pubkey,
+    pub debtor: Pubkey,
+    pub creditor: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub amount_rule: Option<AmountRule>,
+    pub recurrence_interval: u64,
+    pub recurrence_rule: Option<RecurrenceRule>,
+    pub start_at: u64,
+    pub end_at: u64,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct StreamCreated {
+    pub payment: Pubkey,
+    pub debtor: Pubkey,
+    pub creditor: Pubkey,
+    pub mint: Pubkey,
+    pub rate: u64,
+    pub start_at: u64,
+    pub end_at: u64,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct StreamClaimed {
+    pub payment: Pubkey,
+    pub signer: Pubkey,
+    pub amount: u64,
+    pub token_fee: u64,
+    pub claimed_until: u64,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct AllowanceCreated {
+    pub payment: Pubkey,
+    pub debtor: Pubkey,
+    pub creditor: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub period: u64,
+    pub start_at: u64,
+    pub end_at: u64,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct AllowanceCharged {
+    pub payment: Pubkey,
+    pub amount: u64,
+    pub token_fee: u64,
+    pub period_started_at: u64,
+    pub period_spent: u64,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct InvoiceCreated {
+    pub invoice: Pubkey,
+    pub creditor: Pubkey,
+    pub debtor: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub expires_at: u64,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct InvoiceAccepted {
+    pub invoice: Pubkey,
+    pub payment: Pubkey,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct InvoiceClosed {
+    pub invoice: Pubkey,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct PaymentUpdated {
+    pub payment: Pubkey,
+    pub creditor_tokens: Pubkey,
+    pub amount: u64,
+    pub amount_rule: Option<AmountRule>,
+    pub recurrence_interval: u64,
+    pub recurrence_rule: Option<RecurrenceRule>,
+    pub end_at: u64,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct AllowanceRenewed {
+    pub payment: Pubkey,
+    pub delegated_amount: u64,
+    pub transfer_fee: u64,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct PaymentCancelled {
+    pub payment: Pubkey,
+    pub refund: u64,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct PaymentPaused {
+    pub payment: Pubkey,
+    pub signer: Pubkey,
+    pub resume_policy: ResumePolicy,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct PaymentResumed {
+    pub payment: Pubkey,
+    pub signer: Pubkey,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct UsageBilled {
+    pub payment: Pubkey,
+    pub task: Pubkey,
+    pub usage: u64,
+    pub amount: u64,
+    pub unbilled_amount: u64,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct UsageReported {
+    pub payment: Pubkey,
+    pub units: u64,
+    pub usage: u64,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct PaymentIndexCreated {
+    pub index: Pubkey,
+    pub namespace: Pubkey,
+    pub party: Pubkey,
+    pub role: Role,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct VaultCreated {
+    pub vault: Pubkey,
+    pub debtor: Pubkey,
+    pub debtor_tokens: Pubkey,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct TaskIndexCreated {
+    pub index: Pubkey,
+    pub namespace: Pubkey,
+    pub process_at: u64,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct TaskScheduled {
+    pub task: Pubkey,
+    pub payment: Pubkey,
+    pub task_index: Pubkey,
+    pub process_at: u64,
+    pub attempt: u8,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct TaskSkipped {
+    pub task: Pubkey,
+    pub payment: Pubkey,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct TaskExpired {
+    pub task: Pubkey,
+    pub payment: Pubkey,
+    pub refund: u64,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct TransferSucceeded {
+    pub task: Pubkey,
+    pub payment: Pubkey,
+    pub debtor_tokens: Pubkey,
+    pub creditor_tokens: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct TransferFailed {
+    pub task: Pubkey,
+    pub payment: Pubkey,
+    pub reason: FailureReason,
+    pub failure_count: u8,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct FeePaid {
+    pub payment: Pubkey,
+    pub distributor: Pubkey,
+    pub distributor_fee: u64,
+    pub program_fee: u64,
+    pub token_fee: u64,
+    /// The mint the token program fee was paid in.
+    pub mint: Pubkey,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct ConfigUpdated {
+    pub transfer_fee_distributor: u64,
+    pub transfer_fee_program: u64,
+    pub program_fee_bps: u16,
+    pub program_fee_min: u64,
+    pub program_fee_max: u64,
+    pub time_granularity: u64,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct AdminTransferProposed {
+    pub admin: Pubkey,
+    pub pending_admin: Pubkey,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct AdminTransferred {
+    pub prev_admin: Pubkey,
+    pub admin: Pubkey,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct TreasuryWithdrawn {
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct TreasuryTokensWithdrawn {
+    pub destination_tokens: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub timestamp: u64,
+}
@@ -0,0 +1,36 @@
+use {
+    crate::{errors::*, events::*, state::*},
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    #[account(
+        mut,
+        seeds = [SEED_CONFIG],
+        bump = config.bump,
+        constraint = config.pending_admin == Some(pending_admin.key()) @ ErrorCode::InvalidPendingAdmin,
+    )]
+    pub config: Account<'info, Config>,
+
+    pub pending_admin: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<AcceptAdmin>) -> ProgramResult {
+    // Get accounts.
+    let config = &mut ctx.accounts.config;
+    let pending_admin = &ctx.accounts.pending_admin;
+
+    // Hand control of the config to the pending admin.
+    let prev_admin = config.admin;
+    config.admin = pending_admin.key();
+    config.pending_admin = None;
+
+    emit!(AdminTransferred {
+        prev_admin,
+        admin: config.admin,
+        timestamp: Clock::get()?.unix_timestamp as u64,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,291 @@
+use {
+    super::utils::*,
+    crate::{errors::*, events::*, schedule::Schedule, state::*},
+    anchor_lang::{prelude::*, solana_program::system_program},
+    anchor_spl::token::{Mint, Token, TokenAccount},
+    index_program::{program::IndexProgram, state::Index},
+    std::mem::size_of,
+};
+
+#[derive(Accounts)]
+#[instruction(
+    creditor_payment_pointer_bump: u8,
+    creditor_payment_proof_bump: u8,
+    debtor_payment_pointer_bump: u8,
+    debtor_payment_proof_bump: u8,
+    payment_bump: u8,
+    task_bump: u8,
+    task_pointer_bump: u8,
+    task_proof_bump: u8,
+)]
+pub struct AcceptInvoice<'info> {
+    #[account(mut, seeds = [SEED_AUTHORITY], bump = authority.bump)]
+    pub authority: Account<'info, Authority>,
+
+    pub clock: Sysvar<'info, Clock>,
+
+    #[account(seeds = [SEED_CONFIG], bump = config.bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    pub creditor: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        constraint = creditor_payment_index.owner == authority.key(),
+        constraint = creditor_payment_index.namespace == creditor_payment_namespace.key(),
+    )]
+    pub creditor_payment_index: Box<Account<'info, Index>>,
+
+    #[account(
+        seeds = [
+            SEED_NAMESPACE,
+            creditor.key().as_ref(),
+            Role::Creditor.seed(),
+        ],
+        bump = creditor_payment_namespace.bump,
+    )]
+    pub creditor_payment_namespace: Box<Account<'info, Namespace>>,
+
+    #[account(mut)]
+    pub creditor_payment_pointer: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub creditor_payment_proof: AccountInfo<'info>,
+
+    #[account(address = invoice.creditor_tokens)]
+    pub creditor_tokens: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub debtor: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = debtor_payment_index.owner == authority.key(),
+        constraint = debtor_payment_index.namespace == debtor_payment_namespace.key(),
+    )]
+    pub debtor_payment_index: Box<Account<'info, Index>>,
+
+    #[account(
+        seeds = [
+            SEED_NAMESPACE,
+            debtor.key().as_ref(),
+            Role::Debtor.seed(),
+        ],
+        bump = debtor_payment_namespace.bump,
+    )]
+    pub debtor_payment_namespace: Box<Account<'info, Namespace>>,
+
+    #[account(mut)]
+    pub debtor_payment_pointer: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub debtor_payment_proof: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        constraint = debtor_tokens.owner == debtor.key(),
+        constraint = debtor_tokens.mint == mint.key()
+    )]
+    pub debtor_tokens: Account<'info, TokenAccount>,
+
+    #[account(address = index_program::ID)]
+    pub index_program: Program<'info, IndexProgram>,
+
+    #[account(
+        mut,
+        has_one = creditor,
+        has_one = debtor,
+        has_one = mint,
+        constraint = invoice.status == InvoiceStatus::Open @ ErrorCode::InvalidInvoiceStatus,
+        constraint = invoice.expires_at >= clock.unix_timestamp as u64 @ ErrorCode::InvoiceExpired,
+    )]
+    pub invoice: Box<Account<'info, Invoice>>,
+
+    #[account()]
+    pub mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        seeds = [
+            SEED_PAYMENT,
+            debtor_payment_index.key().as_ref(),
+            debtor_payment_index.count.to_string().as_bytes(),
+        ],
+        bump = payment_bump,
+        payer = debtor,
+        space = 8 + size_of::<Payment>(),
+    )]
+    pub payment: Box<Account<'info, Payment>>,
+
+    #[account(address = system_program::ID)]
+    pub system_program: Program<'info, System>,
+
+    #[account(
+        init,
+        seeds = [
+            SEED_TASK,
+            task_index.key().as_ref(),
+            task_index.count.to_string().as_bytes(),
+        ],
+        bump = task_bump,
+        payer = debtor,
+        space = 8 + size_of::<Task>()
+    )]
+    pub task: Account<'info, Task>,
+
+    #[account(
+        mut,
+        constraint = task_index.owner == authority.key(),
+        constraint = task_index.namespace == task_namespace.key(),
+    )]
+    pub task_index: Box<Account<'info, Index>>,
+
+    #[account(
+        seeds = [
+            SEED_TASK_NAMESPACE,
+            invoice.start_at.to_string().as_bytes(),
+        ],
+        bump = task_namespace.bump,
+    )]
+    pub task_namespace: Box<Account<'info, TaskNamespace>>,
+
+    #[account(mut)]
+    pub task_pointer: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub task_proof: AccountInfo<'info>,
+
+    #[account(address = anchor_spl::token::ID)]
+    pub token_program: Program<'info, Token>,
+
+    #[account(
+        mut,
+        seeds = [
+            SEED_VAULT,
+            debtor.key().as_ref(),
+            debtor_tokens.key().as_ref(),
+        ],
+        bump = vault.bump,
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+}
+
+pub fn handler(
+    ctx: Context<AcceptInvoice>,
+    creditor_payment_pointer_bump: u8,
+    creditor_payment_proof_bump: u8,
+    debtor_payment_pointer_bump: u8,
+    debtor_payment_proof_bump: u8,
+    payment_bump: u8,
+    task_bump: u8,
+    task_pointer_bump: u8,
+    task_proof_bump: u8,
+) -> ProgramResult {
+    // Get accounts.
+    let authority = &ctx.accounts.authority;
+    let clock = &ctx.accounts.clock;
+    let config = &ctx.accounts.config;
+    let creditor = &ctx.accounts.creditor;
+    let creditor_tokens = &ctx.accounts.creditor_tokens;
+    let creditor_payment_index = &ctx.accounts.creditor_payment_index;
+    let creditor_payment_pointer = &ctx.accounts.creditor_payment_pointer;
+    let creditor_payment_proof = &ctx.accounts.creditor_payment_proof;
+    let debtor = &mut ctx.accounts.debtor;
+    let debtor_payment_index = &ctx.accounts.debtor_payment_index;
+    let debtor_payment_pointer = &ctx.accounts.debtor_payment_pointer;
+    let debtor_payment_proof = &ctx.accounts.debtor_payment_proof;
+    let debtor_tokens = &mut ctx.accounts.debtor_tokens;
+    let index_program = &ctx.accounts.index_program;
+    let invoice = &mut ctx.accounts.invoice;
+    let mint = &ctx.accounts.mint;
+    let payment = &mut ctx.accounts.payment;
+    let system_program = &ctx.accounts.system_program;
+    let task = &mut ctx.accounts.task;
+    let task_index = &ctx.accounts.task_index;
+    let task_pointer = &ctx.accounts.task_pointer;
+    let task_proof = &ctx.accounts.task_proof;
+    let token_program = &ctx.accounts.token_program;
+    let vault = &mut ctx.accounts.vault;
+
+    // Validate the invoice's schedule and retry policy against the current time granularity.
+    let schedule = Schedule::new(
+        invoice.start_at,
+        invoice.end_at,
+        invoice.recurrence_interval,
+    )
+    .with_rule(invoice.recurrence_rule);
+    schedule.validate(config.time_granularity)?;
+    validate_retries(
+        config.time_granularity,
+        invoice.grace_period,
+        invoice.retry_interval,
+        invoice.max_retries,
+    )?;
+
+    // Save payment data.
+    init_payment(
+        payment,
+        config,
+        debtor_payment_index.count.to_string(),
+        invoice.memo.clone(),
+        debtor.key(),
+        debtor_tokens.key(),
+        creditor.key(),
+        creditor_tokens.key(),
+        mint.key(),
+        PaymentMode::Scheduled,
+        invoice.start_at,
+        invoice.end_at,
+        payment_bump,
+    );
+    payment.amount = invoice.amount;
+    payment.amount_rule = invoice.amount_rule;
+    payment.recurrence_interval = invoice.recurrence_interval;
+    payment.recurrence_rule = invoice.recurrence_rule;
+    payment.grace_period = invoice.grace_period;
+    payment.retry_interval = invoice.retry_interval;
+    payment.max_retries = invoice.max_retries;
+    payment.max_failures = invoice.max_failures;
+
+    // Schedule the payment's first task.
+    schedule_payment(
+        authority,
+        creditor_payment_index,
+        creditor_payment_pointer,
+        creditor_payment_proof,
+        debtor,
+        debtor_payment_index,
+        debtor_payment_pointer,
+        debtor_payment_proof,
+        debtor_tokens,
+        index_program,
+        payment,
+        system_program,
+        task,
+        task_index,
+        task_pointer,
+        task_proof,
+        token_program,
+        vault,
+        creditor_payment_pointer_bump,
+        creditor_payment_proof_bump,
+        debtor_payment_pointer_bump,
+        debtor_payment_proof_bump,
+        task_bump,
+        task_pointer_bump,
+        task_proof_bump,
+        clock.unix_timestamp as u64,
+    )?;
+
+    // Mark the invoice as accepted.
+    invoice.status = InvoiceStatus::Accepted;
+    invoice.payment = Some(payment.key());
+
+    emit!(InvoiceAccepted {
+        invoice: invoice.key(),
+        payment: payment.key(),
+        timestamp: clock.unix_timestamp as u64,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,132 @@
+use {
+    super::utils::*,
+    crate::{errors::*, events::*, schedule::stream_accrued, state::*},
+    anchor_lang::{prelude::*, solana_program::program::invoke, AccountsClose},
+    anchor_spl::token::{Token, TokenAccount},
+    std::cmp::{max, min},
+};
+
+#[derive(Accounts)]
+pub struct CancelPayment<'info> {
+    #[account(mut)]
+    pub debtor: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub debtor_tokens: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        has_one = debtor,
+        has_one = debtor_tokens,
+        constraint = payment.status != PaymentStatus::Cancelled @ ErrorCode::InvalidPaymentStatus,
+    )]
+    pub payment: Box<Account<'info, Payment>>,
+
+    #[account(
+        constraint = signer.key() == payment.debtor || signer.key() == payment.creditor @ ErrorCode::InvalidSigner,
+    )]
+    pub signer: Signer<'info>,
+
+    #[account(address = anchor_spl::token::ID)]
+    pub token_program: Program<'info, Token>,
+
+    #[account(
+        mut,
+        seeds = [
+            SEED_VAULT,
+            debtor.key().as_ref(),
+            debtor_tokens.key().as_ref(),
+        ],
+        bump = vault.bump,
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+}
+
+pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, CancelPayment<'info>>) -> ProgramResult {
+    // Get accounts.
+    let debtor = &ctx.accounts.debtor;
+    let debtor_tokens = &ctx.accounts.debtor_tokens;
+    let payment = &mut ctx.accounts.payment;
+    let signer = &ctx.accounts.signer;
+    let token_program = &ctx.accounts.token_program;
+    let vault = &mut ctx.accounts.vault;
+
+    // A cancelled stream ends now. The tokens it accrued until then, and their token program
+    // fee, stay reserved so the creditor can still claim them.
+    let mut delegated_amount = 0;
+    if payment.mode == PaymentMode::Streaming {
+        let mut end_at = min(payment.end_at, Clock::get()?.unix_timestamp as u64);
+        if payment.status == PaymentStatus::Paused {
+            end_at = min(end_at, payment.paused_at);
+        }
+        payment.end_at = max(end_at, payment.claimed_until);
+        let accrued = stream_accrued(payment.rate, payment.claimed_until, payment.end_at);
+        let token_fee = payment
+            .fees()
+            .cumulative_token_fee(payment.claimed_amount, accrued)?;
+        delegated_amount = min(accrued.saturating_add(token_fee), payment.delegated_amount);
+    }
+
+    // Mark the payment as cancelled.
+    payment.status = PaymentStatus::Cancelled;
+
+    // Release the rest of the payment's reservation in the debtor's vault.
+    reserve(payment, vault, delegated_amount);
+
+    // If the debtor signed, limit the vault's authority to transfer their tokens to what their
+    // other payments reserve, or revoke it if there are none.
+    if signer.key() == debtor.key() && debtor_tokens.delegate == Some(vault.key()).into() {
+        if vault.delegated_amount > 0 {
+            approve_vault(token_program, debtor.clone(), debtor_tokens, vault)?;
+        } else {
+            invoke(
+                &spl_token::instruction::revoke(
+                    &token_program.key(),
+                    &debtor_tokens.key(),
+                    &debtor.key(),
+                    &[],
+                )?,
+                &[
+                    debtor_tokens.to_account_info().clone(),
+                    debtor.clone(),
+                    token_program.to_account_info().clone(),
+                ],
+            )?;
+        }
+    }
+
+    // Refund the unused transfer fees held in the payment account to the debtor.
+    let payment_info = payment.to_account_info();
+    let refund = payment_info
+        .lamports()
+        .saturating_sub(Rent::get()?.minimum_balance(payment_info.data_len()));
+    **payment_info.try_borrow_mut_lamports()? -= refund;
+    **debtor.try_borrow_mut_lamports()? += refund;
+
+    // Close the payment's pending tasks, refunding their rent to whoever paid it: the debtor for
+    // the first task, and the keeper that scheduled it for each later one. Each task is passed
+    // with its rent payer after it, after the instruction's accounts.
+    let tasks = ctx.remaining_accounts.chunks_exact(2);
+    require!(tasks.remainder().is_empty(), ErrorCode::InvalidTask);
+    for accounts in tasks {
+        let (task_info, payer) = (&accounts[0], &accounts[1]);
+        let task = Account::<Task>::try_from(task_info)?;
+        require!(
+            task.payment == payment.key() && task.payer == payer.key(),
+            ErrorCode::InvalidTask
+        );
+        require!(
+            task.status == TaskStatus::Pending,
+            ErrorCode::InvalidTaskStatus
+        );
+        task.close(payer.clone())?;
+    }
+
+    emit!(PaymentCancelled {
+        payment: payment.key(),
+        refund,
+        timestamp: Clock::get()?.unix_timestamp as u64,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,173 @@
+use {
+    super::utils::*,
+    crate::{errors::*, events::*, schedule::allowance_period_start, state::*},
+    anchor_lang::prelude::*,
+    anchor_spl::token::{transfer, Token, TokenAccount, Transfer},
+    index_program::state::Index,
+};
+
+#[derive(Accounts)]
+#[instruction(amount: u64)]
+pub struct Charge<'info> {
+    pub clock: Sysvar<'info, Clock>,
+
+    pub creditor: Signer<'info>,
+
+    #[account(mut)]
+    pub creditor_tokens: Account<'info, TokenAccount>,
+
+    #[account()]
+    pub debtor_payment_index: Account<'info, Index>,
+
+    #[account(mut)]
+    pub debtor_tokens: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+            SEED_PAYMENT,
+            debtor_payment_index.key().as_ref(),
+            payment.id.to_string().as_bytes(),
+        ],
+        bump = payment.bump,
+        has_one = creditor,
+        has_one = creditor_tokens,
+        has_one = debtor_tokens,
+        constraint = payment.mode == PaymentMode::Allowance @ ErrorCode::InvalidPaymentMode,
+        constraint = payment.status == PaymentStatus::Active @ ErrorCode::InvalidPaymentStatus,
+    )]
+    pub payment: Box<Account<'info, Payment>>,
+
+    #[account(address = anchor_spl::token::ID)]
+    pub token_program: Program<'info, Token>,
+
+    #[account(seeds = [SEED_TREASURY], bump = treasury.bump)]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(mut)]
+    pub treasury_tokens: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            SEED_VAULT,
+            payment.debtor.as_ref(),
+            payment.debtor_tokens.as_ref(),
+        ],
+        bump = vault.bump,
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+}
+
+pub fn handler(ctx: Context<Charge>, amount: u64) -> ProgramResult {
+    // Get accounts.
+    let clock = &ctx.accounts.clock;
+    let creditor = &ctx.accounts.creditor;
+    let creditor_tokens = &ctx.accounts.creditor_tokens;
+    let debtor_tokens = &ctx.accounts.debtor_tokens;
+    let payment = &mut ctx.accounts.payment;
+    let token_program = &ctx.accounts.token_program;
+    let treasury = &ctx.accounts.treasury;
+    let treasury_tokens = &ctx.accounts.treasury_tokens;
+    let vault = &mut ctx.accounts.vault;
+
+    // Validate the allowance is open.
+    let now = clock.unix_timestamp as u64;
+    require!(
+        payment.start_at <= now && now < payment.end_at,
+        ErrorCode::InvalidChargeTime
+    );
+
+    // Start a new period if the current one has ended. Unspent allowance does not carry over.
+    let period_started_at =
+        allowance_period_start(payment.start_at, payment.recurrence_interval, now);
+    if period_started_at != payment.period_started_at {
+        payment.period_started_at = period_started_at;
+        payment.period_spent = 0;
+    }
+
+    // Validate the charge fits in what remains of the period's cap. The token program fee is
+    // charged on the period's cumulative spend, as the allowance reserved one fee on each
+    // period's cap.
+    let period_spent = payment
+        .period_spent
+        .checked_add(amount)
+        .ok_or(ErrorCode::AllowanceExceeded)?;
+    require!(
+        amount > 0 && period_spent <= payment.amount,
+        ErrorCode::AllowanceExceeded
+    );
+    let token_fee = payment
+        .fees()
+        .cumulative_token_fee(payment.period_spent, amount)?;
+    let total = amount
+        .checked_add(token_fee)
+        .ok_or(ErrorCode::FeeOverflow)?;
+
+    // Validate the debtor's tokens cover the charge.
+    require!(
+        debtor_tokens.delegate == Some(vault.key()).into()
+            && payment.delegated_amount >= total
+            && debtor_tokens.delegated_amount >= total
+            && debtor_tokens.amount >= total,
+        ErrorCode::InsufficientTokens
+    );
+
+    // Transfer the charged tokens from debtor to creditor.
+    let vault_seeds: &[&[u8]] = &[
+        SEED_VAULT,
+        vault.debtor.as_ref(),
+        vault.debtor_tokens.as_ref(),
+        &[vault.bump],
+    ];
+    transfer(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            Transfer {
+                authority: vault.to_account_info(),
+                from: debtor_tokens.to_account_info(),
+                to: creditor_tokens.to_account_info(),
+            },
+            &[vault_seeds],
+        ),
+        amount,
+    )?;
+
+    // Pay token program fee to treasury.
+    pay_token_fee(
+        token_program,
+        treasury,
+        treasury_tokens,
+        debtor_tokens,
+        vault,
+        payment.mint,
+        token_fee,
+    )?;
+
+    // Save payment data. The transferred tokens are no longer reserved in the vault.
+    payment.delegated_amount -= total;
+    vault.delegated_amount = vault.delegated_amount.saturating_sub(total);
+
+    // Report the fees paid. No lamport fees are charged.
+    emit!(FeePaid {
+        payment: payment.key(),
+        distributor: creditor.key(),
+        distributor_fee: 0,
+        program_fee: 0,
+        token_fee,
+        mint: payment.mint,
+        timestamp: now,
+    });
+    payment.period_spent = period_spent;
+
+    emit!(AllowanceCharged {
+        payment: payment.key(),
+        amount,
+        token_fee,
+        period_started_at,
+        period_spent: payment.period_spent,
+        timestamp: now,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,155 @@
+use {
+    super::utils::*,
+    crate::{errors::*, events::*, schedule::stream_accrued, state::*},
+    anchor_lang::prelude::*,
+    anchor_spl::token::{transfer, Token, TokenAccount, Transfer},
+    index_program::state::Index,
+    std::cmp::min,
+};
+
+#[derive(Accounts)]
+pub struct Claim<'info> {
+    pub clock: Sysvar<'info, Clock>,
+
+    #[account(mut)]
+    pub creditor_tokens: Account<'info, TokenAccount>,
+
+    #[account()]
+    pub debtor_payment_index: Account<'info, Index>,
+
+    #[account(mut)]
+    pub debtor_tokens: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+            SEED_PAYMENT,
+            debtor_payment_index.key().as_ref(),
+            payment.id.to_string().as_bytes(),
+        ],
+        bump = payment.bump,
+        has_one = creditor_tokens,
+        has_one = debtor_tokens,
+        constraint = payment.mode == PaymentMode::Streaming @ ErrorCode::InvalidPaymentMode,
+    )]
+    pub payment: Box<Account<'info, Payment>>,
+
+    pub signer: Signer<'info>,
+
+    #[account(address = anchor_spl::token::ID)]
+    pub token_program: Program<'info, Token>,
+
+    #[account(seeds = [SEED_TREASURY], bump = treasury.bump)]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(mut)]
+    pub treasury_tokens: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            SEED_VAULT,
+            payment.debtor.as_ref(),
+            payment.debtor_tokens.as_ref(),
+        ],
+        bump = vault.bump,
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+}
+
+pub fn handler(ctx: Context<Claim>) -> ProgramResult {
+    // Get accounts.
+    let clock = &ctx.accounts.clock;
+    let creditor_tokens = &ctx.accounts.creditor_tokens;
+    let debtor_tokens = &ctx.accounts.debtor_tokens;
+    let payment = &mut ctx.accounts.payment;
+    let signer = &ctx.accounts.signer;
+    let token_program = &ctx.accounts.token_program;
+    let treasury = &ctx.accounts.treasury;
+    let treasury_tokens = &ctx.accounts.treasury_tokens;
+    let vault = &mut ctx.accounts.vault;
+
+    // Calculate the tokens accrued since the last claim. Nothing accrues while paused, and a
+    // cancelled stream can still be claimed up to the end it was cancelled at.
+    let now = clock.unix_timestamp as u64;
+    let mut until = min(now, payment.end_at);
+    if payment.status == PaymentStatus::Paused {
+        until = min(until, payment.paused_at);
+    }
+    let amount = stream_accrued(payment.rate, payment.claimed_until, until);
+    require!(amount > 0, ErrorCode::NothingToClaim);
+    let token_fee = payment
+        .fees()
+        .cumulative_token_fee(payment.claimed_amount, amount)?;
+    let total = amount
+        .checked_add(token_fee)
+        .ok_or(ErrorCode::FeeOverflow)?;
+
+    // Validate the debtor's tokens cover the claim.
+    require!(
+        debtor_tokens.delegate == Some(vault.key()).into()
+            && payment.delegated_amount >= total
+            && debtor_tokens.delegated_amount >= total
+            && debtor_tokens.amount >= total,
+        ErrorCode::InsufficientTokens
+    );
+
+    // Transfer the accrued tokens from debtor to creditor.
+    let vault_seeds: &[&[u8]] = &[
+        SEED_VAULT,
+        vault.debtor.as_ref(),
+        vault.debtor_tokens.as_ref(),
+        &[vault.bump],
+    ];
+    transfer(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            Transfer {
+                authority: vault.to_account_info(),
+                from: debtor_tokens.to_account_info(),
+                to: creditor_tokens.to_account_info(),
+            },
+            &[vault_seeds],
+        ),
+        amount,
+    )?;
+
+    // Pay token program fee to treasury.
+    pay_token_fee(
+        token_program,
+        treasury,
+        treasury_tokens,
+        debtor_tokens,
+        vault,
+        payment.mint,
+        token_fee,
+    )?;
+
+    // Save payment data. The transferred tokens are no longer reserved in the vault.
+    payment.delegated_amount -= total;
+    vault.delegated_amount = vault.delegated_amount.saturating_sub(total);
+
+    // Report the fees paid. No lamport fees are charged.
+    emit!(FeePaid {
+        payment: payment.key(),
+        distributor: signer.key(),
+        distributor_fee: 0,
+        program_fee: 0,
+        token_fee,
+        mint: payment.mint,
+        timestamp: now,
+    });
+    payment.claimed_until = until;
+    payment.claimed_amount += amount;
+
+    emit!(StreamClaimed {
+        payment: payment.key(),
+        signer: signer.key(),
+        amount,
+        token_fee,
+        claimed_until: until,
+        timestamp: now,
+    });
+
+    Ok(())
+}
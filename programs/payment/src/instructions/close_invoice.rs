@@ -0,0 +1,35 @@
+use {
+    crate::{errors::*, events::*, state::*},
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct CloseInvoice<'info> {
+    pub clock: Sysvar<'info, Clock>,
+
+    #[account(mut)]
+    pub creditor: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = creditor,
+        constraint = invoice.status == InvoiceStatus::Accepted
+            || invoice.expires_at < clock.unix_timestamp as u64 @ ErrorCode::InvoiceNotExpired,
+        close = creditor,
+    )]
+    pub invoice: Account<'info, Invoice>,
+}
+
+pub fn handler(ctx: Context<CloseInvoice>) -> ProgramResult {
+    // Get accounts.
+    let clock = &ctx.accounts.clock;
+    let invoice = &ctx.accounts.invoice;
+
+    // The invoice was either accepted or expired. Its rent is returned to the creditor.
+    emit!(InvoiceClosed {
+        invoice: invoice.key(),
+        timestamp: clock.unix_timestamp as u64,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,146 @@
+use {
+    super::utils::*,
+    crate::{
+        errors::*,
+        events::*,
+        schedule::{AmountRule, RecurrenceRule, Schedule},
+        state::*,
+    },
+    anchor_lang::{prelude::*, solana_program::system_program},
+    anchor_spl::token::{Mint, TokenAccount},
+    std::mem::size_of,
+};
+
+#[derive(Accounts)]
+#[instruction(
+    id: u64,
+    memo: String,
+    amount: u64,
+    amount_rule: Option<AmountRule>,
+    recurrence_interval: u64,
+    recurrence_rule: Option<RecurrenceRule>,
+    start_at: u64,
+    end_at: u64,
+    grace_period: u64,
+    retry_interval: u64,
+    max_retries: u8,
+    max_failures: u8,
+    expires_at: u64,
+    bump: u8,
+)]
+pub struct CreateInvoice<'info> {
+    pub clock: Sysvar<'info, Clock>,
+
+    #[account(seeds = [SEED_CONFIG], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut)]
+    pub creditor: Signer<'info>,
+
+    #[account(
+        constraint = creditor_tokens.owner == creditor.key(),
+        constraint = creditor_tokens.mint == mint.key()
+    )]
+    pub creditor_tokens: Account<'info, TokenAccount>,
+
+    pub debtor: AccountInfo<'info>,
+
+    #[account(
+        init,
+        seeds = [
+            SEED_INVOICE,
+            creditor.key().as_ref(),
+            id.to_string().as_bytes(),
+        ],
+        bump = bump,
+        payer = creditor,
+        space = 8 + size_of::<Invoice>(),
+    )]
+    pub invoice: Account<'info, Invoice>,
+
+    #[account()]
+    pub mint: Account<'info, Mint>,
+
+    #[account(address = system_program::ID)]
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(
+    ctx: Context<CreateInvoice>,
+    id: u64,
+    memo: String,
+    amount: u64,
+    amount_rule: Option<AmountRule>,
+    recurrence_interval: u64,
+    recurrence_rule: Option<RecurrenceRule>,
+    start_at: u64,
+    end_at: u64,
+    grace_period: u64,
+    retry_interval: u64,
+    max_retries: u8,
+    max_failures: u8,
+    expires_at: u64,
+    bump: u8,
+) -> ProgramResult {
+    // Get accounts.
+    let clock = &ctx.accounts.clock;
+    let config = &ctx.accounts.config;
+    let creditor = &ctx.accounts.creditor;
+    let creditor_tokens = &ctx.accounts.creditor_tokens;
+    let debtor = &ctx.accounts.debtor;
+    let invoice = &mut ctx.accounts.invoice;
+    let mint = &ctx.accounts.mint;
+
+    // Validate the invoice's schedule and retry policy, and that it expires before its first
+    // transfer.
+    Schedule::new(start_at, end_at, recurrence_interval)
+        .with_rule(recurrence_rule)
+        .validate(config.time_granularity)?;
+    if let Some(amount_rule) = amount_rule {
+        amount_rule.validate()?;
+    }
+    validate_retries(
+        config.time_granularity,
+        grace_period,
+        retry_interval,
+        max_retries,
+    )?;
+    require!(
+        clock.unix_timestamp as u64 <= expires_at && expires_at <= start_at,
+        ErrorCode::InvalidChronology
+    );
+
+    // Save invoice data.
+    invoice.id = id;
+    invoice.memo = memo;
+    invoice.creditor = creditor.key();
+    invoice.creditor_tokens = creditor_tokens.key();
+    invoice.debtor = debtor.key();
+    invoice.mint = mint.key();
+    invoice.amount = amount;
+    invoice.amount_rule = amount_rule;
+    invoice.recurrence_interval = recurrence_interval;
+    invoice.recurrence_rule = recurrence_rule;
+    invoice.start_at = start_at;
+    invoice.end_at = end_at;
+    invoice.grace_period = grace_period;
+    invoice.retry_interval = retry_interval;
+    invoice.max_retries = max_retries;
+    invoice.max_failures = max_failures;
+    invoice.expires_at = expires_at;
+    invoice.status = InvoiceStatus::Open;
+    invoice.payment = None;
+    invoice.bump = bump;
+
+    emit!(InvoiceCreated {
+        invoice: invoice.key(),
+        creditor: creditor.key(),
+        debtor: debtor.key(),
+        mint: mint.key(),
+        amount,
+        expires_at,
+        timestamp: clock.unix_timestamp as u64,
+    });
+
+    Ok(())
+}
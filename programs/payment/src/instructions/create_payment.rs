@@ -1,26 +1,32 @@
 use {
-    crate::{errors::*, state::*},
-    anchor_lang::{
-        prelude::*,
-        solana_program::{program::invoke, system_instruction, system_program},
+    super::utils::*,
+    crate::{
+        errors::*,
+        schedule::{AmountRule, RecurrenceRule, Schedule, MAX_SPLITS},
+        state::*,
     },
-    anchor_spl::token::{approve, Approve, Mint, Token, TokenAccount},
-    // index_program::{
-    //     cpi::{accounts::CreatePointer, create_pointer},
-    //     program::IndexProgram,
-    //     state::*,
-    // },
+    anchor_lang::{prelude::*, solana_program::system_program},
+    anchor_spl::token::{Mint, Token, TokenAccount},
+    index_program::{program::IndexProgram, state::Index},
     std::mem::size_of,
 };
 
 #[derive(Accounts)]
 #[instruction(
-    id: String,
     memo: String,
     amount: u64,
+    amount_rule: Option<AmountRule>,
     recurrence_interval: u64,
+    recurrence_rule: Option<RecurrenceRule>,
     start_at: u64,
     end_at: u64,
+    grace_period: u64,
+    retry_interval: u64,
+    max_retries: u8,
+    max_failures: u8,
+    splits: Option<Splits>,
+    mode: PaymentMode,
+    unit_price: u64,
     creditor_payment_pointer_bump: u8,
     creditor_payment_proof_bump: u8,
     debtor_payment_pointer_bump: u8,
@@ -31,8 +37,7 @@ use {
     task_proof_bump: u8,
 )]
 pub struct CreatePayment<'info> {
-
-    #[account(seeds = [SEED_AUTHORITY], bump = authority.bump)]
+    #[account(mut, seeds = [SEED_AUTHORITY], bump = authority.bump)]
     pub authority: Account<'info, Authority>,
 
     pub clock: Sysvar<'info, Clock>,
@@ -42,41 +47,28 @@ pub struct CreatePayment<'info> {
 
     pub creditor: AccountInfo<'info>,
 
-    // #[account(
-    //     mut,
-    //     seeds = [
-    //         SEED_INDEX, 
-    //         authority.key().as_ref(), 
-    //         payment_index_namespace(creditor.key(), Role::Creditor).as_bytes(),
-    //     ],
-    //     bump = creditor_payment_index.bump,
-    //     owner = index_program::ID,
-    // )]
-    // pub creditor_payment_index: Account<'info, Index>,
-
-    // #[account(
-    //     mut,
-    //     seeds = [
-    //         SEED_POINTER, 
-    //         creditor_payment_index.key().as_ref(), 
-    //         creditor_payment_index.count.to_string().as_bytes()
-    //     ],
-    //     bump = creditor_payment_pointer_bump,
-    //     owner = index_program::ID,
-    // )]
-    // pub creditor_payment_pointer: Account<'info, Pointer>,
-
-    // #[account(
-    //     mut,
-    //     seeds = [
-    //         SEED_PROOF, 
-    //         creditor_payment_index.key().as_ref(), 
-    //         payment.key().as_ref(),
-    //     ],
-    //     bump = creditor_payment_proof_bump,
-    //     owner = index_program::ID,
-    // )]
-    // pub creditor_payment_proof: Account<'info, Proof>,
+    #[account(
+        mut,
+        constraint = creditor_payment_index.owner == authority.key(),
+        constraint = creditor_payment_index.namespace == creditor_payment_namespace.key(),
+    )]
+    pub creditor_payment_index: Box<Account<'info, Index>>,
+
+    #[account(
+        seeds = [
+            SEED_NAMESPACE,
+            creditor.key().as_ref(),
+            Role::Creditor.seed(),
+        ],
+        bump = creditor_payment_namespace.bump,
+    )]
+    pub creditor_payment_namespace: Box<Account<'info, Namespace>>,
+
+    #[account(mut)]
+    pub creditor_payment_pointer: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub creditor_payment_proof: AccountInfo<'info>,
 
     #[account(
         constraint = creditor_tokens.owner == creditor.key(),
@@ -87,41 +79,28 @@ pub struct CreatePayment<'info> {
     #[account(mut)]
     pub debtor: Signer<'info>,
 
-    // #[account(
-    //     mut,
-    //     seeds = [
-    //         SEED_INDEX, 
-    //         authority.key().as_ref(), 
-    //         payment_index_namespace(debtor.key(), Role::Debtor).as_bytes(),
-    //     ],
-    //     bump = debtor_payment_index.bump,
-    //     owner = index_program::ID,
-    // )]
-    // pub debtor_payment_index: Account<'info, Index>,
-
-    // #[account(
-    //     mut,
-    //     seeds = [
-    //         SEED_POINTER, 
-    //         debtor_payment_index.key().as_ref(), 
-    //         debtor_payment_index.count.to_string().as_bytes()
-    //     ],
-    //     bump = debtor_payment_pointer_bump,
-    //     owner = index_program::ID,
-    // )]
-    // pub debtor_payment_pointer: Account<'info, Pointer>,
-
-    // #[account(
-    //     mut,
-    //     seeds = [
-    //         SEED_PROOF, 
-    //         debtor_payment_index.key().as_ref(), 
-    //         payment.key().as_ref(),
-    //     ],
-    //     bump = debtor_payment_proof_bump,
-    //     owner = index_program::ID,
-    // )]
-    // pub debtor_payment_proof: Account<'info, Proof>,
+    #[account(
+        mut,
+        constraint = debtor_payment_index.owner == authority.key(),
+        constraint = debtor_payment_index.namespace == debtor_payment_namespace.key(),
+    )]
+    pub debtor_payment_index: Box<Account<'info, Index>>,
+
+    #[account(
+        seeds = [
+            SEED_NAMESPACE,
+            debtor.key().as_ref(),
+            Role::Debtor.seed(),
+        ],
+        bump = debtor_payment_namespace.bump,
+    )]
+    pub debtor_payment_namespace: Box<Account<'info, Namespace>>,
+
+    #[account(mut)]
+    pub debtor_payment_pointer: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub debtor_payment_proof: AccountInfo<'info>,
 
     #[account(
         mut,
@@ -130,8 +109,8 @@ pub struct CreatePayment<'info> {
     )]
     pub debtor_tokens: Account<'info, TokenAccount>,
 
-    // #[account(address = index_program::ID)]
-    // pub index_program: Program<'info, IndexProgram>,
+    #[account(address = index_program::ID)]
+    pub index_program: Program<'info, IndexProgram>,
 
     #[account()]
     pub mint: Account<'info, Mint>,
@@ -140,9 +119,9 @@ pub struct CreatePayment<'info> {
         init,
         seeds = [
             SEED_PAYMENT,
-            debtor.key().as_ref(),
-            // debtor_payment_index.count.to_string().as_bytes(),
-        ], 
+            debtor_payment_index.key().as_ref(),
+            debtor_payment_index.count.to_string().as_bytes(),
+        ],
         bump = payment_bump,
         payer = debtor,
         space = 8 + size_of::<Payment>(),
@@ -155,9 +134,9 @@ pub struct CreatePayment<'info> {
     #[account(
         init,
         seeds = [
-            SEED_TASK, 
-            // task_index.key().as_ref(), 
-            // task_index.count.to_string().as_bytes()
+            SEED_TASK,
+            task_index.key().as_ref(),
+            task_index.count.to_string().as_bytes(),
         ],
         bump = task_bump,
         payer = debtor,
@@ -165,215 +144,191 @@ pub struct CreatePayment<'info> {
     )]
     pub task: Account<'info, Task>,
 
-    // #[account(
-    //     mut,
-    //     seeds = [
-    //         SEED_INDEX, 
-    //         authority.key().as_ref(), 
-    //         task_index_namespace(start_at).as_bytes(),
-    //     ],
-    //     bump = task_index.bump,
-    //     owner = index_program::ID,
-    // )]
-    // pub task_index: Account<'info, Index>,
-
-    // #[account(
-    //     mut,
-    //     seeds = [
-    //         SEED_POINTER, 
-    //         task_index.key().as_ref(), 
-    //         task_index.count.to_string().as_bytes(),
-    //     ],
-    //     bump = task_pointer_bump,
-    //     owner = index_program::ID,
-    // )]
-    // pub task_pointer: Account<'info, Pointer>,
-
-    // #[account(
-    //     mut,
-    //     seeds = [
-    //         SEED_PROOF, 
-    //         task_index.key().as_ref(), 
-    //         task.key().as_ref(),
-    //     ],
-    //     bump = task_proof_bump,
-    //     owner = index_program::ID,
-    // )]
-    // pub task_proof: Account<'info, Proof>,
+    #[account(
+        mut,
+        constraint = task_index.owner == authority.key(),
+        constraint = task_index.namespace == task_namespace.key(),
+    )]
+    pub task_index: Box<Account<'info, Index>>,
+
+    #[account(
+        seeds = [
+            SEED_TASK_NAMESPACE,
+            start_at.to_string().as_bytes(),
+        ],
+        bump = task_namespace.bump,
+    )]
+    pub task_namespace: Box<Account<'info, TaskNamespace>>,
+
+    #[account(mut)]
+    pub task_pointer: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub task_proof: AccountInfo<'info>,
 
     #[account(address = anchor_spl::token::ID)]
     pub token_program: Program<'info, Token>,
+
+    #[account(
+        mut,
+        seeds = [
+            SEED_VAULT,
+            debtor.key().as_ref(),
+            debtor_tokens.key().as_ref(),
+        ],
+        bump = vault.bump,
+    )]
+    pub vault: Box<Account<'info, Vault>>,
 }
 
 pub fn handler(
     ctx: Context<CreatePayment>,
     memo: String,
     amount: u64,
+    amount_rule: Option<AmountRule>,
     recurrence_interval: u64,
+    recurrence_rule: Option<RecurrenceRule>,
     start_at: u64,
     end_at: u64,
-    _creditor_payment_pointer_bump: u8,
-    _creditor_payment_proof_bump: u8,
-    _debtor_payment_pointer_bump: u8,
-    _debtor_payment_proof_bump: u8,
+    grace_period: u64,
+    retry_interval: u64,
+    max_retries: u8,
+    max_failures: u8,
+    splits: Option<Splits>,
+    mode: PaymentMode,
+    unit_price: u64,
+    creditor_payment_pointer_bump: u8,
+    creditor_payment_proof_bump: u8,
+    debtor_payment_pointer_bump: u8,
+    debtor_payment_proof_bump: u8,
     payment_bump: u8,
     task_bump: u8,
-    _task_pointer_bump: u8,
-    _task_proof_bump: u8,
+    task_pointer_bump: u8,
+    task_proof_bump: u8,
 ) -> ProgramResult {
     // Get accounts.
-    let _authority = &ctx.accounts.authority;
+    let authority = &ctx.accounts.authority;
+    let clock = &ctx.accounts.clock;
     let config = &ctx.accounts.config;
     let creditor = &ctx.accounts.creditor;
     let creditor_tokens = &ctx.accounts.creditor_tokens;
-    // let creditor_payment_index = &mut ctx.accounts.creditor_payment_index;
-    // let creditor_payment_pointer = &ctx.accounts.creditor_payment_pointer;
-    // let creditor_payment_proof = &ctx.accounts.creditor_payment_proof;
+    let creditor_payment_index = &ctx.accounts.creditor_payment_index;
+    let creditor_payment_pointer = &ctx.accounts.creditor_payment_pointer;
+    let creditor_payment_proof = &ctx.accounts.creditor_payment_proof;
     let debtor = &mut ctx.accounts.debtor;
-    // let debtor_payment_index = &mut ctx.accounts.debtor_payment_index;
-    // let debtor_payment_pointer = &ctx.accounts.debtor_payment_pointer;
-    // let debtor_payment_proof = &ctx.accounts.debtor_payment_proof;
+    let debtor_payment_index = &ctx.accounts.debtor_payment_index;
+    let debtor_payment_pointer = &ctx.accounts.debtor_payment_pointer;
+    let debtor_payment_proof = &ctx.accounts.debtor_payment_proof;
     let debtor_tokens = &mut ctx.accounts.debtor_tokens;
-    // let index_program = &ctx.accounts.index_program;
+    let index_program = &ctx.accounts.index_program;
     let mint = &ctx.accounts.mint;
     let payment = &mut ctx.accounts.payment;
     let system_program = &ctx.accounts.system_program;
     let task = &mut ctx.accounts.task;
-    // let task_index = &mut ctx.accounts.task_index;
-    // let task_pointer = &mut ctx.accounts.task_pointer;
-    // let task_proof = &mut ctx.accounts.task_proof;
+    let task_index = &ctx.accounts.task_index;
+    let task_pointer = &ctx.accounts.task_pointer;
+    let task_proof = &ctx.accounts.task_proof;
     let token_program = &ctx.accounts.token_program;
+    let vault = &mut ctx.accounts.vault;
 
     // Validate payment chronology.
-    match recurrence_interval {
-        0 => require!(start_at == end_at, ErrorCode::InvalidChronology), // One-time payment
-        _ => require!(start_at <= end_at, ErrorCode::InvalidChronology)  // Recurring payment
+    let schedule = Schedule::new(start_at, end_at, recurrence_interval).with_rule(recurrence_rule);
+    schedule.validate(config.time_granularity)?;
+    if let Some(amount_rule) = amount_rule {
+        amount_rule.validate()?;
     }
 
-    // TODO validate the recurrence interval is in units of minutes
-    // TODO validate the recurrence interval falls within the alloted time window
+    // Validate the payment mode. Only metered payments are priced per unit of usage.
+    match mode {
+        PaymentMode::Scheduled => require!(unit_price == 0, ErrorCode::InvalidUnitPrice),
+        PaymentMode::Metered => require!(unit_price > 0, ErrorCode::InvalidUnitPrice),
+        _ => return Err(ErrorCode::InvalidPaymentMode.into()),
+    }
 
-    // Calculate expected number of transfers.
-    let num_transfers = match recurrence_interval {
-        0 => 1,                                         // One-time payment
-        _ => (end_at - start_at) / recurrence_interval, // Recurring payment 
-    };
+    // Validate the retry policy.
+    validate_retries(
+        config.time_granularity,
+        grace_period,
+        retry_interval,
+        max_retries,
+    )?;
 
-    // Calculate the transfer fee. 
-    let transfer_fee = num_transfers * (
-            config.transfer_fee_distributor + 
-            config.transfer_fee_program
+    // Validate the split table. Its recipients' token accounts are passed in order, after the
+    // instruction's accounts, and must hold the payment's mint.
+    let mut split_tokens = [Pubkey::default(); MAX_SPLITS];
+    if let Some(splits) = splits {
+        splits.validate()?;
+        require!(
+            ctx.remaining_accounts.len() == splits.len as usize,
+            ErrorCode::InvalidSplitTokens
         );
-
-    // Validate debtor has sufficient lamports to cover transfer fee.
-    require!(
-        debtor.to_account_info().lamports() >= transfer_fee,
-        ErrorCode::InsufficientBalance
-    );
+        for (i, split_tokens_info) in ctx.remaining_accounts.iter().enumerate() {
+            let split_tokens_data = Account::<TokenAccount>::try_from(split_tokens_info)?;
+            require!(
+                split_tokens_data.mint == mint.key(),
+                ErrorCode::InvalidSplitTokens
+            );
+            split_tokens[i] = split_tokens_info.key();
+        }
+    }
 
     // Save payment data.
-    // payment.id = debtor_payment_index.count.to_string(); 
-    payment.memo = memo;
-    payment.debtor = debtor.key();
-    payment.debtor_tokens = debtor_tokens.key();
-    payment.creditor = creditor.key();
-    payment.creditor_tokens = creditor_tokens.key();
-    payment.mint = mint.key();
+    init_payment(
+        payment,
+        config,
+        debtor_payment_index.count.to_string(),
+        memo,
+        debtor.key(),
+        debtor_tokens.key(),
+        creditor.key(),
+        creditor_tokens.key(),
+        mint.key(),
+        mode,
+        start_at,
+        end_at,
+        payment_bump,
+    );
     payment.amount = amount;
+    payment.amount_rule = amount_rule;
     payment.recurrence_interval = recurrence_interval;
-    payment.start_at = start_at;
-    payment.end_at = end_at;
-    payment.bump = payment_bump;
-
-    // Save task data.
-    // task.id = task_index.count.to_string(); 
-    task.payment = payment.key();
-    task.status = TaskStatus::Pending;
-    task.bump = task_bump;
-
-    // Authorize payment account to transfer debtor's tokens.
-    approve(
-        CpiContext::new(
-            token_program.to_account_info(),
-            Approve {
-                authority: debtor.to_account_info(),
-                delegate: payment.to_account_info(),
-                to: debtor_tokens.to_account_info(),
-            },
-        ),
-        num_transfers * amount,
-    )?;
-
-    // Collect transfer fee from debtor. Hold funds in payment account.
-    invoke(
-        &system_instruction::transfer(
-            &debtor.key(), 
-            &payment.key(), 
-            transfer_fee
-        ),
-        &[
-            debtor.to_account_info().clone(),
-            payment.to_account_info().clone(),
-            system_program.to_account_info().clone(),
-        ],
+    payment.recurrence_rule = recurrence_rule;
+    payment.grace_period = grace_period;
+    payment.retry_interval = retry_interval;
+    payment.max_retries = max_retries;
+    payment.max_failures = max_failures;
+    payment.unit_price = unit_price;
+    payment.splits = splits;
+    payment.split_tokens = split_tokens;
+
+    // Schedule the payment's first task.
+    schedule_payment(
+        authority,
+        creditor_payment_index,
+        creditor_payment_pointer,
+        creditor_payment_proof,
+        debtor,
+        debtor_payment_index,
+        debtor_payment_pointer,
+        debtor_payment_proof,
+        debtor_tokens,
+        index_program,
+        payment,
+        system_program,
+        task,
+        task_index,
+        task_pointer,
+        task_proof,
+        token_program,
+        vault,
+        creditor_payment_pointer_bump,
+        creditor_payment_proof_bump,
+        debtor_payment_pointer_bump,
+        debtor_payment_proof_bump,
+        task_bump,
+        task_pointer_bump,
+        task_proof_bump,
+        clock.unix_timestamp as u64,
     )?;
 
-    // Create pointer to payment in creditor's payment index.
-    // create_pointer(
-    //     CpiContext::new_with_signer(
-    //         index_program.to_account_info(), 
-    //         CreatePointer {
-    //             index: creditor_payment_index.to_account_info(),
-    //             pointer: creditor_payment_pointer.to_account_info(),
-    //             proof: creditor_payment_proof.to_account_info(),
-    //             owner: authority.to_account_info(),
-    //             system_program: system_program.to_account_info(),
-    //         },
-    //         &[&[SEED_AUTHORITY, &[authority.bump]]]
-    //     ), 
-    //     creditor_payment_index.count.to_string(), 
-    //     payment.key(), 
-    //     creditor_payment_pointer_bump, 
-    //     creditor_payment_proof_bump
-    // )?;
-
-    // Create pointer to payment in debtor's payment index.
-    // create_pointer(
-    //     CpiContext::new_with_signer(
-    //         index_program.to_account_info(), 
-    //         CreatePointer {
-    //             index: debtor_payment_index.to_account_info(),
-    //             pointer: debtor_payment_pointer.to_account_info(),
-    //             proof: debtor_payment_proof.to_account_info(),
-    //             owner: authority.to_account_info(),
-    //             system_program: system_program.to_account_info(),
-    //         },
-    //         &[&[SEED_AUTHORITY, &[authority.bump]]]
-    //     ), 
-    //     debtor_payment_index.count.to_string(), 
-    //     payment.key(), 
-    //     debtor_payment_pointer_bump, 
-    //     debtor_payment_proof_bump
-    // )?;
-
-    // Create pointer to task in time-bound task index.
-    // create_pointer(
-    //     CpiContext::new_with_signer(
-    //         index_program.to_account_info(), 
-    //         CreatePointer {
-    //             index: task_index.to_account_info(),
-    //             pointer: task_pointer.to_account_info(),
-    //             proof: task_proof.to_account_info(),
-    //             owner: authority.to_account_info(),
-    //             system_program: system_program.to_account_info(),
-    //         },
-    //         &[&[SEED_AUTHORITY, &[authority.bump]]]
-    //     ), 
-    //     task_index.count.to_string(), 
-    //     task.key(), 
-    //     task_pointer_bump, 
-    //     task_proof_bump
-    // )?;
-
-    return Ok(());
+    Ok(())
 }
@@ -1,11 +1,12 @@
 use {
-    crate::state::*,
+    crate::{events::*, state::*},
     anchor_lang::{prelude::*, solana_program::system_program},
     std::mem::size_of,
 };
 
 #[derive(Accounts)]
 #[instruction(
+    role: Role,
     index_bump: u8,
     namespace_bump: u8
 )]
@@ -24,6 +25,7 @@ pub struct CreatePaymentIndex<'info> {
         seeds = [
             SEED_NAMESPACE,
             party.key().as_ref(),
+            role.seed(),
         ],
         bump = namespace_bump,
         payer = payer,
@@ -43,6 +45,7 @@ pub struct CreatePaymentIndex<'info> {
 
 pub fn handler(
     ctx: Context<CreatePaymentIndex>,
+    role: Role,
     index_bump: u8,
     namespace_bump: u8,
 ) -> ProgramResult {
@@ -57,9 +60,17 @@ pub fn handler(
 
     // Initialize namespace account.
     namespace.party = party.key();
-    namespace.role = Role::Creditor;
+    namespace.role = role.clone();
     namespace.bump = namespace_bump;
 
+    emit!(PaymentIndexCreated {
+        index: index.key(),
+        namespace: namespace.key(),
+        party: party.key(),
+        role,
+        timestamp: Clock::get()?.unix_timestamp as u64,
+    });
+
     // Create an index to lookup payments by (party, role) pairs.
     // (e.g. all the payments where Alice is a creditor or Bob is a debtor)
     index_program::cpi::create_index(
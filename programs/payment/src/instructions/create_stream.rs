@@ -0,0 +1,247 @@
+use {
+    super::utils::*,
+    crate::{errors::*, events::*, state::*},
+    anchor_lang::{prelude::*, solana_program::system_program},
+    anchor_spl::token::{Mint, Token, TokenAccount},
+    index_program::{program::IndexProgram, state::Index},
+    std::mem::size_of,
+};
+
+#[derive(Accounts)]
+#[instruction(
+    memo: String,
+    rate: u64,
+    start_at: u64,
+    end_at: u64,
+    creditor_payment_pointer_bump: u8,
+    creditor_payment_proof_bump: u8,
+    debtor_payment_pointer_bump: u8,
+    debtor_payment_proof_bump: u8,
+    payment_bump: u8,
+)]
+pub struct CreateStream<'info> {
+    #[account(mut, seeds = [SEED_AUTHORITY], bump = authority.bump)]
+    pub authority: Account<'info, Authority>,
+
+    pub clock: Sysvar<'info, Clock>,
+
+    #[account(seeds = [SEED_CONFIG], bump = config.bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    pub creditor: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        constraint = creditor_payment_index.owner == authority.key(),
+        constraint = creditor_payment_index.namespace == creditor_payment_namespace.key(),
+    )]
+    pub creditor_payment_index: Box<Account<'info, Index>>,
+
+    #[account(
+        seeds = [
+            SEED_NAMESPACE,
+            creditor.key().as_ref(),
+            Role::Creditor.seed(),
+        ],
+        bump = creditor_payment_namespace.bump,
+    )]
+    pub creditor_payment_namespace: Box<Account<'info, Namespace>>,
+
+    #[account(mut)]
+    pub creditor_payment_pointer: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub creditor_payment_proof: AccountInfo<'info>,
+
+    #[account(
+        constraint = creditor_tokens.owner == creditor.key(),
+        constraint = creditor_tokens.mint == mint.key()
+    )]
+    pub creditor_tokens: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub debtor: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = debtor_payment_index.owner == authority.key(),
+        constraint = debtor_payment_index.namespace == debtor_payment_namespace.key(),
+    )]
+    pub debtor_payment_index: Box<Account<'info, Index>>,
+
+    #[account(
+        seeds = [
+            SEED_NAMESPACE,
+            debtor.key().as_ref(),
+            Role::Debtor.seed(),
+        ],
+        bump = debtor_payment_namespace.bump,
+    )]
+    pub debtor_payment_namespace: Box<Account<'info, Namespace>>,
+
+    #[account(mut)]
+    pub debtor_payment_pointer: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub debtor_payment_proof: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        constraint = debtor_tokens.owner == debtor.key(),
+        constraint = debtor_tokens.mint == mint.key()
+    )]
+    pub debtor_tokens: Account<'info, TokenAccount>,
+
+    #[account(address = index_program::ID)]
+    pub index_program: Program<'info, IndexProgram>,
+
+    #[account()]
+    pub mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        seeds = [
+            SEED_PAYMENT,
+            debtor_payment_index.key().as_ref(),
+            debtor_payment_index.count.to_string().as_bytes(),
+        ],
+        bump = payment_bump,
+        payer = debtor,
+        space = 8 + size_of::<Payment>(),
+    )]
+    pub payment: Box<Account<'info, Payment>>,
+
+    #[account(address = system_program::ID)]
+    pub system_program: Program<'info, System>,
+
+    #[account(address = anchor_spl::token::ID)]
+    pub token_program: Program<'info, Token>,
+
+    #[account(
+        mut,
+        seeds = [
+            SEED_VAULT,
+            debtor.key().as_ref(),
+            debtor_tokens.key().as_ref(),
+        ],
+        bump = vault.bump,
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+}
+
+pub fn handler(
+    ctx: Context<CreateStream>,
+    memo: String,
+    rate: u64,
+    start_at: u64,
+    end_at: u64,
+    creditor_payment_pointer_bump: u8,
+    creditor_payment_proof_bump: u8,
+    debtor_payment_pointer_bump: u8,
+    debtor_payment_proof_bump: u8,
+    payment_bump: u8,
+) -> ProgramResult {
+    // Get accounts.
+    let authority = &ctx.accounts.authority;
+    let clock = &ctx.accounts.clock;
+    let config = &ctx.accounts.config;
+    let creditor = &ctx.accounts.creditor;
+    let creditor_payment_index = &ctx.accounts.creditor_payment_index;
+    let creditor_payment_pointer = &ctx.accounts.creditor_payment_pointer;
+    let creditor_payment_proof = &ctx.accounts.creditor_payment_proof;
+    let creditor_tokens = &ctx.accounts.creditor_tokens;
+    let debtor = &ctx.accounts.debtor;
+    let debtor_payment_index = &ctx.accounts.debtor_payment_index;
+    let debtor_payment_pointer = &ctx.accounts.debtor_payment_pointer;
+    let debtor_payment_proof = &ctx.accounts.debtor_payment_proof;
+    let debtor_tokens = &ctx.accounts.debtor_tokens;
+    let index_program = &ctx.accounts.index_program;
+    let mint = &ctx.accounts.mint;
+    let payment = &mut ctx.accounts.payment;
+    let system_program = &ctx.accounts.system_program;
+    let token_program = &ctx.accounts.token_program;
+    let vault = &mut ctx.accounts.vault;
+
+    // Validate the stream.
+    require!(rate > 0, ErrorCode::InvalidRate);
+    require!(start_at < end_at, ErrorCode::InvalidChronology);
+
+    // Save payment data. Streams are claimed rather than processed, so they hold no lamport fees.
+    init_payment(
+        payment,
+        config,
+        debtor_payment_index.count.to_string(),
+        memo,
+        debtor.key(),
+        debtor_tokens.key(),
+        creditor.key(),
+        creditor_tokens.key(),
+        mint.key(),
+        PaymentMode::Streaming,
+        start_at,
+        end_at,
+        payment_bump,
+    );
+    payment.transfer_fee_distributor = 0;
+    payment.transfer_fee_program = 0;
+    payment.rate = rate;
+    payment.claimed_until = start_at;
+
+    // Reserve the stream's tokens, including the token program fees, in the debtor's vault.
+    let total = rate
+        .checked_mul(end_at - start_at)
+        .ok_or(ErrorCode::InvalidRate)?;
+    let delegated_amount = total
+        .checked_add(payment.fees().cumulative_token_fee(0, total)?)
+        .ok_or(ErrorCode::FeeOverflow)?;
+    reserve(payment, vault, delegated_amount);
+
+    // Authorize the vault to transfer the tokens reserved by all of the debtor's payments.
+    approve_vault(
+        token_program,
+        debtor.to_account_info(),
+        debtor_tokens,
+        vault,
+    )?;
+
+    // Create pointer to payment in creditor's payment index.
+    index_account(
+        authority,
+        index_program,
+        creditor_payment_index,
+        creditor_payment_pointer,
+        creditor_payment_proof,
+        debtor.to_account_info(),
+        system_program,
+        payment.key(),
+        creditor_payment_pointer_bump,
+        creditor_payment_proof_bump,
+    )?;
+
+    // Create pointer to payment in debtor's payment index.
+    index_account(
+        authority,
+        index_program,
+        debtor_payment_index,
+        debtor_payment_pointer,
+        debtor_payment_proof,
+        debtor.to_account_info(),
+        system_program,
+        payment.key(),
+        debtor_payment_pointer_bump,
+        debtor_payment_proof_bump,
+    )?;
+
+    emit!(StreamCreated {
+        payment: payment.key(),
+        debtor: debtor.key(),
+        creditor: creditor.key(),
+        mint: mint.key(),
+        rate,
+        start_at,
+        end_at,
+        timestamp: clock.unix_timestamp as u64,
+    });
+
+    Ok(())
+}
@@ -1,19 +1,18 @@
 use {
-    super::utils::*,
-    // index_program::program::IndexProgram,
-    // index_program::{
-    //     cpi::{accounts::CreateIndex, create_index},
-    //     program::IndexProgram,
-    //     state::*,
-    // },
-    crate::{errors, state::*},
+    crate::{errors, events::*, state::*},
     anchor_lang::{prelude::*, solana_program::system_program},
+    index_program::{
+        cpi::{accounts::CreateIndex, create_index},
+        program::IndexProgram,
+    },
+    std::mem::size_of,
 };
 
 #[derive(Accounts)]
 #[instruction(
     process_at: u64,
-    bump: u8,
+    index_bump: u8,
+    namespace_bump: u8,
 )]
 pub struct CreateTaskIndex<'info> {
     #[account(mut, seeds = [SEED_AUTHORITY], bump = authority.bump)]
@@ -21,29 +20,60 @@ pub struct CreateTaskIndex<'info> {
 
     pub clock: Sysvar<'info, Clock>,
 
-    // #[account(address = index_program::ID)]
-    // pub index_program: Program<'info, IndexProgram>,
+    #[account(seeds = [SEED_CONFIG], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut)]
+    pub index: AccountInfo<'info>,
+
+    #[account(address = index_program::ID)]
+    pub index_program: Program<'info, IndexProgram>,
+
+    #[account(
+        init,
+        seeds = [
+            SEED_TASK_NAMESPACE,
+            process_at.to_string().as_bytes(),
+        ],
+        bump = namespace_bump,
+        payer = signer,
+        space = 8 + size_of::<TaskNamespace>(),
+    )]
+    pub namespace: Account<'info, TaskNamespace>,
+
     #[account(mut)]
     pub signer: Signer<'info>,
 
     #[account(address = system_program::ID)]
     pub system_program: Program<'info, System>,
-    // #[account(mut)]
-    // pub task_index: Account<'info, Index>,
 }
 
-pub fn handler(ctx: Context<CreateTaskIndex>, process_at: u64, _bump: u8) -> ProgramResult {
+pub fn handler(
+    ctx: Context<CreateTaskIndex>,
+    process_at: u64,
+    index_bump: u8,
+    namespace_bump: u8,
+) -> ProgramResult {
     // Get accounts.
-    let _authority = &ctx.accounts.authority;
+    let authority = &ctx.accounts.authority;
     let clock = &ctx.accounts.clock;
-    // let index_program = &ctx.accounts.index_program;
-    let _system_program = &ctx.accounts.system_program;
-    // let task_index = &ctx.accounts.task_index;
+    let config = &ctx.accounts.config;
+    let index = &ctx.accounts.index;
+    let index_program = &ctx.accounts.index_program;
+    let namespace = &mut ctx.accounts.namespace;
+    let signer = &ctx.accounts.signer;
+    let system_program = &ctx.accounts.system_program;
 
-    // Validate process_at is at the top of the minute.
+    // Validate process_at is at the start of a time bucket. Payments schedule their tasks in
+    // buckets of the time granularity they were created with, so a payment passed after the
+    // instruction's accounts lets its buckets be created after the granularity has changed.
+    let time_granularity = match ctx.remaining_accounts.first() {
+        Some(payment_info) => Account::<Payment>::try_from(payment_info)?.time_granularity,
+        None => config.time_granularity,
+    };
     require!(
-        process_at % ONE_MINUTE == 0,
-        errors::ErrorCode::InvalidProcessAtIntraMinute
+        process_at.checked_rem(time_granularity) == Some(0),
+        errors::ErrorCode::InvalidProcessAtGranularity
     );
 
     // Validate process_at is not in the past.
@@ -52,21 +82,30 @@ pub fn handler(ctx: Context<CreateTaskIndex>, process_at: u64, _bump: u8) -> Pro
         errors::ErrorCode::InvalidProcessAtPast
     );
 
-    // Create an index to lookup tasks by process_at time.
-    // create_index(
-    //     CpiContext::new_with_signer(
-    //         index_program.to_account_info(),
-    //         CreateIndex {
-    //             index: task_index.to_account_info(),
-    //             owner: authority.to_account_info(),
-    //             system_program: system_program.to_account_info(),
-    //         },
-    //         &[&[SEED_AUTHORITY, &[authority.bump]]],
-    //     ),
-    //     task_index_namespace(process_at),
-    //     true,
-    //     bump,
-    // )
+    // Initialize namespace account.
+    namespace.process_at = process_at;
+    namespace.bump = namespace_bump;
 
-    return Ok(());
+    // Create an index to lookup tasks by process_at time.
+    emit!(TaskIndexCreated {
+        index: index.key(),
+        namespace: namespace.key(),
+        process_at,
+        timestamp: clock.unix_timestamp as u64,
+    });
+    create_index(
+        CpiContext::new_with_signer(
+            index_program.to_account_info(),
+            CreateIndex {
+                index: index.to_account_info(),
+                namespace: namespace.to_account_info(),
+                owner: authority.to_account_info(),
+                payer: signer.to_account_info(),
+                system_program: system_program.to_account_info(),
+            },
+            &[&[SEED_AUTHORITY, &[authority.bump]]],
+        ),
+        true,
+        index_bump,
+    )
 }
@@ -0,0 +1,54 @@
+use {
+    crate::{events::*, state::*},
+    anchor_lang::{prelude::*, solana_program::system_program},
+    anchor_spl::token::TokenAccount,
+    std::mem::size_of,
+};
+
+#[derive(Accounts)]
+#[instruction(bump: u8)]
+pub struct CreateVault<'info> {
+    #[account(mut)]
+    pub debtor: Signer<'info>,
+
+    #[account(constraint = debtor_tokens.owner == debtor.key())]
+    pub debtor_tokens: Account<'info, TokenAccount>,
+
+    #[account(address = system_program::ID)]
+    pub system_program: Program<'info, System>,
+
+    #[account(
+        init,
+        seeds = [
+            SEED_VAULT,
+            debtor.key().as_ref(),
+            debtor_tokens.key().as_ref(),
+        ],
+        bump = bump,
+        payer = debtor,
+        space = 8 + size_of::<Vault>(),
+    )]
+    pub vault: Account<'info, Vault>,
+}
+
+pub fn handler(ctx: Context<CreateVault>, bump: u8) -> ProgramResult {
+    // Get accounts.
+    let debtor = &ctx.accounts.debtor;
+    let debtor_tokens = &ctx.accounts.debtor_tokens;
+    let vault = &mut ctx.accounts.vault;
+
+    // Initialize vault account.
+    vault.debtor = debtor.key();
+    vault.debtor_tokens = debtor_tokens.key();
+    vault.delegated_amount = 0;
+    vault.bump = bump;
+
+    emit!(VaultCreated {
+        vault: vault.key(),
+        debtor: debtor.key(),
+        debtor_tokens: debtor_tokens.key(),
+        timestamp: Clock::get()?.unix_timestamp as u64,
+    });
+
+    Ok(())
+}
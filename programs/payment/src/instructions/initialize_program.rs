@@ -1,9 +1,10 @@
 use {
     anchor_lang::{
         prelude::*,
-        solana_program::system_program
+        solana_program::{bpf_loader_upgradeable, system_program}
     },
-    crate::state,
+    super::utils::*,
+    crate::{errors::*, state},
     std::mem::size_of
 };
 
@@ -19,6 +20,12 @@ pub struct InitializeProgram<'info> {
     #[account(mut)]
     pub signer: Signer<'info>,
 
+    #[account(
+        address = Pubkey::find_program_address(&[crate::ID.as_ref()], &bpf_loader_upgradeable::ID).0,
+        constraint = program_data.upgrade_authority_address == Some(signer.key()) @ ErrorCode::InvalidAdmin,
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
     #[account(
         init,
         seeds = [state::SEED_AUTHORITY],
@@ -61,18 +68,30 @@ pub fn handler(
     // Get accounts.
     let authority = &mut ctx.accounts.authority;
     let config = &mut ctx.accounts.config;
+    let signer = &ctx.accounts.signer;
     let treasury = &mut ctx.accounts.treasury;
 
     // Initialize authority account.
     authority.bump = authority_bump;
 
-    // Initialize config account.
+    // Initialize config account. The signer, who must be the program's upgrade authority,
+    // becomes the program admin.
+    config.admin = signer.key();
+    config.pending_admin = None;
     config.transfer_fee_distributor = config_transfer_fee_distributor;
     config.transfer_fee_program = config_transfer_fee_program;
+    config.program_fee_bps = 0;
+    config.program_fee_min = 0;
+    config.program_fee_max = 0;
+    config.time_granularity = ONE_MINUTE;
     config.bump = config_bump;
 
     // Initialize treasury account.
+    treasury.total_program_fees_collected = 0;
+    treasury.total_distributor_fees_paid = 0;
+    treasury.total_transfers_processed = 0;
+    treasury.total_withdrawn = 0;
     treasury.bump = treasury_bump;
 
-    return Ok(());
+    Ok(())
 }
@@ -1,13 +1,50 @@
+pub mod accept_admin;
+pub mod accept_invoice;
+pub mod cancel_payment;
+pub mod charge;
+pub mod claim;
+pub mod close_invoice;
+pub mod create_allowance;
+pub mod create_invoice;
 pub mod create_payment;
 pub mod create_payment_index;
+pub mod create_stream;
 pub mod create_task_index;
+pub mod create_vault;
 pub mod initialize_program;
+pub mod pause_payment;
 pub mod process_task;
+pub mod renew_allowance;
+pub mod report_usage;
+pub mod resume_payment;
+pub mod transfer_admin;
+pub mod update_config;
+pub mod update_payment;
 pub mod utils;
+pub mod withdraw_treasury;
+pub mod withdraw_treasury_tokens;
 
+pub use accept_admin::*;
+pub use accept_invoice::*;
+pub use cancel_payment::*;
+pub use charge::*;
+pub use claim::*;
+pub use close_invoice::*;
+pub use create_allowance::*;
+pub use create_invoice::*;
 pub use create_payment::*;
 pub use create_payment_index::*;
+pub use create_stream::*;
 pub use create_task_index::*;
+pub use create_vault::*;
 pub use initialize_program::*;
+pub use pause_payment::*;
 pub use process_task::*;
-pub use utils::*;
+pub use renew_allowance::*;
+pub use report_usage::*;
+pub use resume_payment::*;
+pub use transfer_admin::*;
+pub use update_config::*;
+pub use update_payment::*;
+pub use withdraw_treasury::*;
+pub use withdraw_treasury_tokens::*;
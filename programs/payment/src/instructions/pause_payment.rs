@@ -0,0 +1,43 @@
+use {
+    crate::{errors::*, events::*, state::*},
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+#[instruction(resume_policy: ResumePolicy)]
+pub struct PausePayment<'info> {
+    pub clock: Sysvar<'info, Clock>,
+
+    #[account(
+        mut,
+        constraint = payment.status == PaymentStatus::Active @ ErrorCode::InvalidPaymentStatus,
+        constraint = payment.mode == PaymentMode::Streaming || payment.schedule().is_recurring() @ ErrorCode::InvalidPauseOneTime,
+    )]
+    pub payment: Box<Account<'info, Payment>>,
+
+    #[account(
+        constraint = signer.key() == payment.debtor || signer.key() == payment.creditor @ ErrorCode::InvalidSigner,
+    )]
+    pub signer: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<PausePayment>, resume_policy: ResumePolicy) -> ProgramResult {
+    // Get accounts.
+    let clock = &ctx.accounts.clock;
+    let payment = &mut ctx.accounts.payment;
+    let signer = &ctx.accounts.signer;
+
+    // Mark the payment as paused.
+    payment.status = PaymentStatus::Paused;
+    payment.paused_at = clock.unix_timestamp as u64;
+    payment.resume_policy = resume_policy.clone();
+
+    emit!(PaymentPaused {
+        payment: payment.key(),
+        signer: signer.key(),
+        resume_policy,
+        timestamp: clock.unix_timestamp as u64,
+    });
+
+    Ok(())
+}
@@ -1,16 +1,30 @@
 use {
-    crate::state,
-    anchor_lang::{prelude::*, solana_program::system_program},
+    super::utils::*,
+    crate::{errors::*, events::*, schedule::process_at, state},
+    anchor_lang::{
+        prelude::*,
+        solana_program::{program::invoke_signed, system_instruction, system_program},
+    },
     anchor_spl::token::{transfer, Token, TokenAccount, Transfer},
-    index_program::state::*,
+    index_program::{
+        cpi::{accounts::CreatePointer, create_pointer},
+        program::IndexProgram,
+        state::*,
+    },
+    std::mem::size_of,
 };
 
 #[derive(Accounts)]
+#[instruction(
+    next_task_bump: u8,
+    next_task_pointer_bump: u8,
+    next_task_proof_bump: u8,
+)]
 pub struct ProcessTask<'info> {
-    pub clock: Sysvar<'info, Clock>,
+    #[account(mut, seeds = [state::SEED_AUTHORITY], bump = authority.bump)]
+    pub authority: Box<Account<'info, state::Authority>>,
 
-    #[account(seeds = [state::SEED_CONFIG], bump = config.bump)]
-    pub config: Account<'info, state::Config>,
+    pub clock: Sysvar<'info, Clock>,
 
     pub creditor: AccountInfo<'info>,
 
@@ -34,6 +48,24 @@ pub struct ProcessTask<'info> {
     )]
     pub debtor_tokens: Account<'info, TokenAccount>,
 
+    #[account(address = index_program::ID)]
+    pub index_program: Program<'info, IndexProgram>,
+
+    #[account(mut)]
+    pub next_task: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub next_task_index: AccountInfo<'info>,
+
+    #[account()]
+    pub next_task_namespace: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub next_task_pointer: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub next_task_proof: AccountInfo<'info>,
+
     #[account(
         mut,
         seeds = [
@@ -64,73 +96,512 @@ pub struct ProcessTask<'info> {
         ],
         bump = task.bump,
         has_one = payment,
+        constraint = task.status == state::TaskStatus::Pending @ ErrorCode::InvalidTaskStatus,
     )]
     pub task: Account<'info, state::Task>,
 
+    #[account(
+        constraint = task_index.owner == authority.key(),
+        constraint = task_index.namespace == task_namespace.key(),
+    )]
+    pub task_index: Box<Account<'info, Index>>,
+
     #[account(
         seeds = [
-            state::SEED_TASK_INDEX,
-            task_index.process_at.to_string().as_bytes()
+            state::SEED_TASK_NAMESPACE,
+            task_namespace.process_at.to_string().as_bytes()
         ],
-        bump = task_index.bump,
+        bump = task_namespace.bump,
     )]
-    pub task_index: Box<Account<'info, state::TaskIndex>>,
+    pub task_namespace: Box<Account<'info, state::TaskNamespace>>,
 
     #[account(mut, seeds = [state::SEED_TREASURY], bump = treasury.bump)]
     pub treasury: Account<'info, state::Treasury>,
 
+    #[account(mut)]
+    pub treasury_tokens: AccountInfo<'info>,
+
     #[account(address = anchor_spl::token::ID)]
     pub token_program: Program<'info, Token>,
+
+    #[account(
+        mut,
+        seeds = [
+            state::SEED_VAULT,
+            payment.debtor.as_ref(),
+            payment.debtor_tokens.as_ref(),
+        ],
+        bump = vault.bump,
+    )]
+    pub vault: Box<Account<'info, state::Vault>>,
 }
 
-pub fn handler(ctx: Context<ProcessTask>) -> ProgramResult {
+pub fn handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, ProcessTask<'info>>,
+    next_task_bump: u8,
+    next_task_pointer_bump: u8,
+    next_task_proof_bump: u8,
+) -> ProgramResult {
     // Get accounts.
+    let authority = &ctx.accounts.authority;
+    let clock = &ctx.accounts.clock;
     let payment = &mut ctx.accounts.payment;
-    let debtor_payment_index = &ctx.accounts.debtor_payment_index;
     let debtor_tokens = &ctx.accounts.debtor_tokens;
     let creditor_tokens = &ctx.accounts.creditor_tokens;
+    let debtor = &ctx.accounts.debtor;
+    let index_program = &ctx.accounts.index_program;
+    let next_task = &ctx.accounts.next_task;
+    let next_task_index = &ctx.accounts.next_task_index;
+    let next_task_namespace = &ctx.accounts.next_task_namespace;
+    let next_task_pointer = &ctx.accounts.next_task_pointer;
+    let next_task_proof = &ctx.accounts.next_task_proof;
     let signer = &ctx.accounts.signer;
-    let config = &ctx.accounts.config;
-    let treasury = &ctx.accounts.treasury;
+    let system_program = &ctx.accounts.system_program;
+    let task = &mut ctx.accounts.task;
+    let task_namespace = &ctx.accounts.task_namespace;
+    let treasury = &mut ctx.accounts.treasury;
+    let treasury_tokens = &ctx.accounts.treasury_tokens;
     let token_program = &ctx.accounts.token_program;
+    let vault = &mut ctx.accounts.vault;
+
+    // Validate the task is due.
+    let now = clock.unix_timestamp as u64;
+    require!(
+        task_namespace.process_at <= now,
+        ErrorCode::InvalidProcessAtFuture
+    );
+
+    // Record when the task was processed. Each branch below sets its final status.
+    task.processed_at = now;
+    task.processed_slot = clock.slot;
+
+    // Process the task. Pick the time of the task to schedule next, if any.
+    let transfer_fee = payment.fees().lamports_per_transfer()?;
+    let reserved = |payment: &state::Payment, occurrence_index| {
+        payment
+            .fees()
+            .total_tokens(payment.amount, payment.amount_rule, occurrence_index, 1)
+    };
+    // Occurrences that are already due are scheduled into the earliest bucket that can still be
+    // created, so a late task never leaves the payment without a next task.
+    let time_granularity = payment.time_granularity;
+    let mut next_task_at =
+        payment
+            .schedule()
+            .next_occurrence(task.occurrence_at)
+            .map(|next_occurrence_at| {
+                (
+                    process_at(next_occurrence_at, now, time_granularity),
+                    next_occurrence_at,
+                    0,
+                )
+            });
+    if payment.status == state::PaymentStatus::Cancelled {
+        // The task was left pending when its payment was cancelled. Cancel it too, without
+        // attempting a transfer or scheduling a next task.
+        task.status = state::TaskStatus::Cancelled;
+        next_task_at = None;
+    } else if payment.status == state::PaymentStatus::Paused {
+        // Skip the task without charging transfer fees. Under the shift policy, make up the
+        // skipped transfer by extending the payment's schedule. Under the continue policy, the
+        // skipped transfer is forgone so its tokens are released and its transfer fee is refunded to
+        // the debtor.
+        task.status = state::TaskStatus::Skipped;
+        emit!(TaskSkipped {
+            task: task.key(),
+            payment: payment.key(),
+            timestamp: now,
+        });
+        match payment.resume_policy {
+            state::ResumePolicy::Shift => {
+                payment.end_at = payment.schedule().extended_end_at(task.occurrence_at);
+
+                // The skipped transfer is made up by a new last transfer, whose amount may
+                // differ under an amount rule. Reserve its tokens in place of the skipped one's.
+                let last_occurrence_index = task.occurrence_index
+                    + payment.schedule().num_transfers_from(task.occurrence_at)
+                    - 1;
+                let added = reserved(payment, last_occurrence_index);
+                let released = reserved(payment, task.occurrence_index);
+                let delegated_amount = payment
+                    .delegated_amount
+                    .saturating_sub(released)
+                    .saturating_add(added);
+                reserve(payment, vault, delegated_amount);
+                next_task_at = payment.schedule().next_occurrence(task.occurrence_at).map(
+                    |next_occurrence_at| {
+                        (
+                            process_at(next_occurrence_at, now, time_granularity),
+                            next_occurrence_at,
+                            0,
+                        )
+                    },
+                );
+            }
+            state::ResumePolicy::Continue => {
+                let released = reserved(payment, task.occurrence_index);
+                release(payment, vault, released);
+                **payment.to_account_info().try_borrow_mut_lamports()? -= transfer_fee;
+                **debtor.to_account_info().try_borrow_mut_lamports()? += transfer_fee;
+            }
+        }
+    } else if payment.grace_period > 0 && now > task.occurrence_at + payment.grace_period {
+        // The task missed its grace period. Expire it, release its tokens, and refund its transfer
+        // fee to the debtor.
+        task.status = state::TaskStatus::Expired;
+        let released = reserved(payment, task.occurrence_index);
+        release(payment, vault, released);
+        **payment.to_account_info().try_borrow_mut_lamports()? -= transfer_fee;
+        **debtor.to_account_info().try_borrow_mut_lamports()? += transfer_fee;
+        emit!(TaskExpired {
+            task: task.key(),
+            payment: payment.key(),
+            refund: transfer_fee,
+            timestamp: now,
+        });
+    } else {
+        // Check if transfer is valid. The debtor's tokens must cover the token program fee too.
+        // A metered payment without usage transfers nothing and pays no token program fee.
+        let amount = payment.amount_due(task.occurrence_index);
+        let token_fee = match amount {
+            0 => 0,
+            _ => payment.fees().token_fee(amount),
+        };
+        // Transfers are made by the debtor's vault, out of the tokens the payment reserved in it.
+        let failure_reason = if debtor_tokens.delegate != Some(vault.key()).into() {
+            Some(state::FailureReason::InvalidDelegate)
+        } else if payment.delegated_amount < amount + token_fee
+            || debtor_tokens.delegated_amount < amount + token_fee
+        {
+            Some(state::FailureReason::InsufficientAllowance)
+        } else if debtor_tokens.amount < amount + token_fee {
+            Some(state::FailureReason::InsufficientBalance)
+        } else {
+            None
+        };
+
+        match failure_reason {
+            None => {
+                let vault_seeds: &[&[u8]] = &[
+                    state::SEED_VAULT,
+                    vault.debtor.as_ref(),
+                    vault.debtor_tokens.as_ref(),
+                    &[vault.bump],
+                ];
+
+                // Transfer each split recipient's share of the tokens. Their token accounts are
+                // passed in order, after the instruction's accounts.
+                let mut creditor_amount = amount;
+                if let Some(splits) = payment.splits {
+                    require!(
+                        ctx.remaining_accounts.len() == splits.len as usize,
+                        ErrorCode::InvalidSplitTokens
+                    );
+                    for ((split_tokens, split_tokens_info), share) in payment
+                        .split_tokens
+                        .iter()
+                        .zip(ctx.remaining_accounts.iter())
+                        .zip(splits.shares(amount))
+                    {
+                        require!(
+                            split_tokens_info.key() == *split_tokens,
+                            ErrorCode::InvalidSplitTokens
+                        );
+                        transfer(
+                            CpiContext::new_with_signer(
+                                token_program.to_account_info(),
+                                Transfer {
+                                    authority: vault.to_account_info(),
+                                    from: debtor_tokens.to_account_info(),
+                                    to: split_tokens_info.clone(),
+                                },
+                                &[vault_seeds],
+                            ),
+                            share,
+                        )?;
+                    }
+                    creditor_amount = splits.remainder(amount);
+                }
+
+                // Transfer the rest of the tokens from debtor to creditor.
+                transfer(
+                    CpiContext::new_with_signer(
+                        token_program.to_account_info(),
+                        Transfer {
+                            authority: vault.to_account_info(),
+                            from: debtor_tokens.to_account_info(),
+                            to: creditor_tokens.to_account_info(),
+                        },
+                        &[vault_seeds],
+                    ),
+                    creditor_amount,
+                )?;
+                task.status = state::TaskStatus::Done;
+                payment.failure_count = 0;
+
+                // The usage is billed. Usage beyond the cap is not carried over, so report what
+                // was left unbilled.
+                if payment.mode == state::PaymentMode::Metered {
+                    emit!(UsageBilled {
+                        payment: payment.key(),
+                        task: task.key(),
+                        usage: payment.usage,
+                        amount,
+                        unbilled_amount: payment
+                            .usage
+                            .saturating_mul(payment.unit_price)
+                            .saturating_sub(amount),
+                        timestamp: now,
+                    });
+                }
+                payment.usage = 0;
+                emit!(TransferSucceeded {
+                    task: task.key(),
+                    payment: payment.key(),
+                    debtor_tokens: debtor_tokens.key(),
+                    creditor_tokens: creditor_tokens.key(),
+                    mint: payment.mint,
+                    amount,
+                    timestamp: now,
+                });
+
+                // Pay transfer fee to distributor.
+                **payment.to_account_info().try_borrow_mut_lamports()? -=
+                    payment.transfer_fee_distributor;
+                **signer.to_account_info().try_borrow_mut_lamports()? +=
+                    payment.transfer_fee_distributor;
+
+                // Pay transfer fee to treasury.
+                **payment.to_account_info().try_borrow_mut_lamports()? -=
+                    payment.transfer_fee_program;
+                **treasury.to_account_info().try_borrow_mut_lamports()? +=
+                    payment.transfer_fee_program;
+
+                // Pay token program fee to treasury.
+                pay_token_fee(
+                    token_program,
+                    treasury,
+                    treasury_tokens,
+                    debtor_tokens,
+                    vault,
+                    payment.mint,
+                    token_fee,
+                )?;
 
-    // TODO If payment is ongoing, create a new task in future task set.
-    // TODO mark task for replication if should repeat
-
-    // Check if transfer is valid.
-    let is_valid = debtor_tokens.delegate.is_some()
-        && debtor_tokens.delegate.unwrap() == payment.key()
-        && debtor_tokens.delegated_amount >= payment.amount
-        && debtor_tokens.amount >= payment.amount;
-
-    // Transfer tokens from debtor to creditor.
-    if is_valid {
-        transfer(
-            CpiContext::new_with_signer(
-                token_program.to_account_info(),
-                Transfer {
-                    authority: payment.to_account_info(),
-                    from: debtor_tokens.to_account_info(),
-                    to: creditor_tokens.to_account_info(),
-                },
-                &[&[
-                    state::SEED_PAYMENT,
-                    debtor_payment_index.key().as_ref(),
-                    payment.id.to_string().as_bytes(),
-                    &[payment.bump],
-                ]],
-            ),
-            payment.amount,
+                // The occurrence's tokens are no longer reserved in the vault. A metered payment
+                // reserves its cap, so whatever the billed usage left of it is released too.
+                let released = reserved(payment, task.occurrence_index);
+                release(payment, vault, released);
+
+                // Record the fees in the treasury's running totals.
+                treasury.total_program_fees_collected += payment.transfer_fee_program;
+                treasury.total_distributor_fees_paid += payment.transfer_fee_distributor;
+                treasury.total_transfers_processed += 1;
+                emit!(FeePaid {
+                    payment: payment.key(),
+                    distributor: signer.key(),
+                    distributor_fee: payment.transfer_fee_distributor,
+                    program_fee: payment.transfer_fee_program,
+                    token_fee,
+                    mint: payment.mint,
+                    timestamp: now,
+                });
+            }
+            Some(reason) => {
+                // Record the failure. No transfer fees are charged for failed attempts.
+                task.status = state::TaskStatus::Failed;
+                payment.failure_count += 1;
+                payment.last_failure_at = now;
+                payment.last_failure_reason = reason.clone();
+                emit!(TransferFailed {
+                    task: task.key(),
+                    payment: payment.key(),
+                    reason,
+                    failure_count: payment.failure_count,
+                    timestamp: now,
+                });
+
+                if payment.max_failures > 0 && payment.failure_count >= payment.max_failures {
+                    // Too many consecutive failures. Cancel the payment, release its reservation
+                    // in the vault, and refund its unused transfer fees to the debtor.
+                    payment.status = state::PaymentStatus::Cancelled;
+                    reserve(payment, vault, 0);
+                    let payment_info = payment.to_account_info();
+                    let refund = payment_info
+                        .lamports()
+                        .saturating_sub(Rent::get()?.minimum_balance(payment_info.data_len()));
+                    **payment_info.try_borrow_mut_lamports()? -= refund;
+                    **debtor.to_account_info().try_borrow_mut_lamports()? += refund;
+                    emit!(PaymentCancelled {
+                        payment: payment.key(),
+                        refund,
+                        timestamp: now,
+                    });
+                    next_task_at = None;
+                } else if task.attempt < payment.max_retries {
+                    // Retry the transfer later, counting from now rather than from the task's
+                    // bucket, which may be long past. Its transfer fee stays held for the retry.
+                    next_task_at = Some((
+                        process_at(now + payment.retry_interval, now, time_granularity),
+                        task.occurrence_at,
+                        task.attempt + 1,
+                    ));
+                } else {
+                    // Give up on this transfer. Release its tokens and refund its transfer fee to
+                    // the debtor.
+                    let released = reserved(payment, task.occurrence_index);
+                    release(payment, vault, released);
+                    **payment.to_account_info().try_borrow_mut_lamports()? -= transfer_fee;
+                    **debtor.to_account_info().try_borrow_mut_lamports()? += transfer_fee;
+                }
+            }
+        }
+    }
+
+    // If the payment is ongoing, schedule the next task. Otherwise, release whatever is left of its
+    // reservation.
+    if next_task_at.is_none() {
+        reserve(payment, vault, 0);
+    }
+    if let Some((next_process_at, next_occurrence_at, next_attempt)) = next_task_at {
+        create_next_task(
+            ctx.program_id,
+            authority,
+            index_program,
+            payment.key(),
+            next_process_at,
+            next_occurrence_at,
+            match next_attempt {
+                0 => task.occurrence_index + 1,
+                _ => task.occurrence_index,
+            },
+            next_attempt,
+            next_task,
+            next_task_bump,
+            next_task_index,
+            next_task_namespace,
+            next_task_pointer,
+            next_task_pointer_bump,
+            next_task_proof,
+            next_task_proof_bump,
+            signer,
+            system_program,
         )?;
     }
 
-    // Pay transfer fee to distributor.
-    **payment.to_account_info().try_borrow_mut_lamports()? -= config.transfer_fee_distributor;
-    **signer.to_account_info().try_borrow_mut_lamports()? += config.transfer_fee_distributor;
+    Ok(())
+}
+
+fn create_next_task<'info>(
+    program_id: &Pubkey,
+    authority: &Account<'info, state::Authority>,
+    index_program: &Program<'info, IndexProgram>,
+    payment: Pubkey,
+    next_process_at: u64,
+    next_occurrence_at: u64,
+    next_occurrence_index: u64,
+    next_attempt: u8,
+    next_task: &AccountInfo<'info>,
+    next_task_bump: u8,
+    next_task_index: &AccountInfo<'info>,
+    next_task_namespace: &AccountInfo<'info>,
+    next_task_pointer: &AccountInfo<'info>,
+    next_task_pointer_bump: u8,
+    next_task_proof: &AccountInfo<'info>,
+    next_task_proof_bump: u8,
+    signer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+) -> ProgramResult {
+    // Validate the next task namespace is the one for the next process_at time.
+    let next_task_namespace_data = Account::<state::TaskNamespace>::try_from(next_task_namespace)?;
+    require!(
+        next_task_namespace_data.process_at == next_process_at
+            && Pubkey::create_program_address(
+                &[
+                    state::SEED_TASK_NAMESPACE,
+                    next_process_at.to_string().as_bytes(),
+                    &[next_task_namespace_data.bump],
+                ],
+                program_id,
+            ) == Ok(next_task_namespace.key()),
+        ErrorCode::InvalidTaskIndex
+    );
+
+    // Validate the next task index is the program's index for that namespace.
+    let next_task_index_data = Account::<Index>::try_from(next_task_index)?;
+    require!(
+        next_task_index_data.owner == authority.key()
+            && next_task_index_data.namespace == next_task_namespace.key(),
+        ErrorCode::InvalidTaskIndex
+    );
+
+    // Create the next task account at the next position in the task index.
+    let next_task_id = next_task_index_data.count.to_string();
+    invoke_signed(
+        &system_instruction::create_account(
+            &signer.key(),
+            &next_task.key(),
+            Rent::get()?.minimum_balance(8 + size_of::<state::Task>()),
+            (8 + size_of::<state::Task>()) as u64,
+            program_id,
+        ),
+        &[
+            signer.to_account_info().clone(),
+            next_task.clone(),
+            system_program.to_account_info().clone(),
+        ],
+        &[&[
+            state::SEED_TASK,
+            next_task_index.key().as_ref(),
+            next_task_id.as_bytes(),
+            &[next_task_bump],
+        ]],
+    )?;
 
-    // Pay transfer fee to treasury.
-    **payment.to_account_info().try_borrow_mut_lamports()? -= config.transfer_fee_program;
-    **treasury.to_account_info().try_borrow_mut_lamports()? += config.transfer_fee_program;
+    // Save next task data.
+    let next_task_data = state::Task {
+        id: next_task_id.clone(),
+        payment,
+        payer: signer.key(),
+        process_at: next_process_at,
+        occurrence_at: next_occurrence_at,
+        occurrence_index: next_occurrence_index,
+        attempt: next_attempt,
+        status: state::TaskStatus::Pending,
+        processed_at: 0,
+        processed_slot: 0,
+        bump: next_task_bump,
+    };
+    let mut data = next_task.try_borrow_mut_data()?;
+    let mut cursor = std::io::Cursor::new(&mut data[..]);
+    next_task_data.try_serialize(&mut cursor)?;
+    drop(data);
+    emit!(TaskScheduled {
+        task: next_task.key(),
+        payment,
+        task_index: next_task_index.key(),
+        process_at: next_process_at,
+        attempt: next_attempt,
+        timestamp: Clock::get()?.unix_timestamp as u64,
+    });
 
-    return Ok(());
+    // Create pointer to the next task in its time-bound task index.
+    create_pointer(
+        CpiContext::new_with_signer(
+            index_program.to_account_info(),
+            CreatePointer {
+                index: next_task_index.clone(),
+                pointer: next_task_pointer.clone(),
+                proof: next_task_proof.clone(),
+                owner: authority.to_account_info(),
+                payer: signer.to_account_info(),
+                system_program: system_program.to_account_info(),
+            },
+            &[&[state::SEED_AUTHORITY, &[authority.bump]]],
+        ),
+        next_task_id,
+        next_task.key(),
+        next_task_pointer_bump,
+        next_task_proof_bump,
+    )
 }
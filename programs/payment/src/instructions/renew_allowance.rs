@@ -0,0 +1,163 @@
+use {
+    super::utils::*,
+    crate::{
+        errors::*,
+        events::*,
+        schedule::{allowance_num_periods, allowance_period_start, stream_accrued},
+        state::*,
+    },
+    anchor_lang::{prelude::*, solana_program::system_program},
+    anchor_spl::token::{Token, TokenAccount},
+};
+
+#[derive(Accounts)]
+pub struct RenewAllowance<'info> {
+    #[account(seeds = [SEED_CONFIG], bump = config.bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(mut)]
+    pub debtor: Signer<'info>,
+
+    #[account(mut)]
+    pub debtor_tokens: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        has_one = debtor,
+        has_one = debtor_tokens,
+        constraint = payment.status != PaymentStatus::Cancelled @ ErrorCode::InvalidPaymentStatus,
+    )]
+    pub payment: Box<Account<'info, Payment>>,
+
+    #[account(address = system_program::ID)]
+    pub system_program: Program<'info, System>,
+
+    #[account(address = anchor_spl::token::ID)]
+    pub token_program: Program<'info, Token>,
+
+    #[account(
+        mut,
+        seeds = [
+            SEED_VAULT,
+            debtor.key().as_ref(),
+            debtor_tokens.key().as_ref(),
+        ],
+        bump = vault.bump,
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+}
+
+pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, RenewAllowance<'info>>) -> ProgramResult {
+    // Get accounts.
+    let config = &ctx.accounts.config;
+    let debtor = &ctx.accounts.debtor;
+    let debtor_tokens = &ctx.accounts.debtor_tokens;
+    let payment = &mut ctx.accounts.payment;
+    let system_program = &ctx.accounts.system_program;
+    let token_program = &ctx.accounts.token_program;
+    let vault = &mut ctx.accounts.vault;
+
+    // Adopt the current fees for the rest of the payment. Streams and allowances are not
+    // processed by distributors, so they keep holding no lamport fees.
+    if payment.mode == PaymentMode::Scheduled || payment.mode == PaymentMode::Metered {
+        payment.transfer_fee_distributor = config.transfer_fee_distributor;
+        payment.transfer_fee_program = config.transfer_fee_program;
+    }
+    payment.program_fee_bps = config.program_fee_bps;
+    payment.program_fee_min = config.program_fee_min;
+    payment.program_fee_max = config.program_fee_max;
+
+    // Calculate the tokens the rest of the payment needs, and the number of transfers whose
+    // lamport fees it holds.
+    let fees = payment.fees();
+    let now = Clock::get()?.unix_timestamp as u64;
+    let (delegated_amount, num_transfers) = match payment.mode {
+        PaymentMode::Streaming => {
+            // The tokens accrued since the last claim are still owed, along with the rest of the
+            // stream. The token program fee is charged on the stream's cumulative claims.
+            let amount = stream_accrued(payment.rate, payment.claimed_until, payment.end_at);
+            let token_fee = fees.cumulative_token_fee(payment.claimed_amount, amount)?;
+            let delegated_amount = amount
+                .checked_add(token_fee)
+                .ok_or(ErrorCode::FeeOverflow)?;
+            (delegated_amount, 0)
+        }
+        PaymentMode::Allowance => {
+            // What remains of the current period's cap, then the cap of each later period.
+            let period = payment.recurrence_interval;
+            let mut delegated_amount = 0;
+            let mut next_period_at = payment.start_at;
+            if now >= payment.start_at {
+                let period_started_at = allowance_period_start(payment.start_at, period, now);
+                let period_spent = match period_started_at == payment.period_started_at {
+                    true => payment.period_spent,
+                    false => 0,
+                };
+                if now < payment.end_at {
+                    let amount = payment.amount.saturating_sub(period_spent);
+                    delegated_amount = amount
+                        .checked_add(fees.cumulative_token_fee(period_spent, amount)?)
+                        .ok_or(ErrorCode::FeeOverflow)?;
+                }
+                next_period_at = period_started_at.saturating_add(period);
+            }
+            let num_periods = allowance_num_periods(next_period_at, payment.end_at, period);
+            let delegated_amount = delegated_amount
+                .checked_add(fees.total_tokens(payment.amount, None, 0, num_periods))
+                .ok_or(ErrorCode::FeeOverflow)?;
+            (delegated_amount, 0)
+        }
+        PaymentMode::Scheduled | PaymentMode::Metered => {
+            // The payment's pending task is passed after the instruction's accounts. The
+            // remaining transfers start from it.
+            let task_info = ctx
+                .remaining_accounts
+                .first()
+                .ok_or(ErrorCode::InvalidTask)?;
+            let task = Account::<Task>::try_from(task_info)?;
+            require!(task.payment == payment.key(), ErrorCode::InvalidTask);
+            require!(
+                task.status == TaskStatus::Pending,
+                ErrorCode::InvalidTaskStatus
+            );
+            let num_transfers = payment.schedule().num_transfers_from(task.occurrence_at);
+            let delegated_amount = fees.total_tokens(
+                payment.amount,
+                payment.amount_rule,
+                task.occurrence_index,
+                num_transfers,
+            );
+            (delegated_amount, num_transfers)
+        }
+    };
+
+    // Reserve exactly the tokens the rest of the payment needs in the debtor's vault, in place
+    // of the payment's previous reservation.
+    reserve(payment, vault, delegated_amount);
+
+    // Authorize the vault to transfer the tokens reserved by all of the debtor's payments.
+    approve_vault(
+        token_program,
+        debtor.to_account_info(),
+        debtor_tokens,
+        vault,
+    )?;
+
+    // Settle the difference between the escrowed and the required transfer fees.
+    let transfer_fee = fees.total_lamports(num_transfers)?;
+    settle_escrow(
+        payment.to_account_info(),
+        debtor.to_account_info(),
+        system_program,
+        transfer_fee,
+    )?;
+
+    emit!(AllowanceRenewed {
+        payment: payment.key(),
+        delegated_amount,
+        transfer_fee,
+        timestamp: now,
+    });
+
+    Ok(())
+}
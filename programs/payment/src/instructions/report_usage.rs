@@ -0,0 +1,41 @@
+use {
+    crate::{errors::*, events::*, state::*},
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+#[instruction(units: u64)]
+pub struct ReportUsage<'info> {
+    pub clock: Sysvar<'info, Clock>,
+
+    pub creditor: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = creditor,
+        constraint = payment.mode == PaymentMode::Metered @ ErrorCode::InvalidPaymentMode,
+        constraint = payment.status != PaymentStatus::Cancelled @ ErrorCode::InvalidPaymentStatus,
+    )]
+    pub payment: Box<Account<'info, Payment>>,
+}
+
+pub fn handler(ctx: Context<ReportUsage>, units: u64) -> ProgramResult {
+    // Get accounts.
+    let clock = &ctx.accounts.clock;
+    let payment = &mut ctx.accounts.payment;
+
+    // Validate the usage report.
+    require!(units > 0, ErrorCode::InvalidUsage);
+
+    // Accumulate the usage. It stays visible to the debtor until the next transfer bills it.
+    payment.usage = payment.usage.saturating_add(units);
+
+    emit!(UsageReported {
+        payment: payment.key(),
+        units,
+        usage: payment.usage,
+        timestamp: clock.unix_timestamp as u64,
+    });
+
+    Ok(())
+}
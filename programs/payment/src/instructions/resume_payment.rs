@@ -0,0 +1,50 @@
+use {
+    crate::{errors::*, events::*, schedule::stream_resumed_claimed_until, state::*},
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct ResumePayment<'info> {
+    #[account(
+        mut,
+        constraint = payment.status == PaymentStatus::Paused @ ErrorCode::InvalidPaymentStatus,
+    )]
+    pub payment: Box<Account<'info, Payment>>,
+
+    #[account(
+        constraint = signer.key() == payment.debtor || signer.key() == payment.creditor @ ErrorCode::InvalidSigner,
+    )]
+    pub signer: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<ResumePayment>) -> ProgramResult {
+    // Get accounts.
+    let payment = &mut ctx.accounts.payment;
+    let signer = &ctx.accounts.signer;
+
+    // Streams do not accrue while paused. Under the shift policy, make up the paused time by
+    // extending the stream's end.
+    if payment.mode == PaymentMode::Streaming {
+        let claimed_until = stream_resumed_claimed_until(
+            payment.claimed_until,
+            payment.paused_at,
+            Clock::get()?.unix_timestamp as u64,
+        );
+        if payment.resume_policy == ResumePolicy::Shift {
+            payment.end_at += claimed_until - payment.claimed_until;
+        }
+        payment.claimed_until = claimed_until;
+    }
+
+    // Mark the payment as active.
+    payment.status = PaymentStatus::Active;
+    payment.paused_at = 0;
+
+    emit!(PaymentResumed {
+        payment: payment.key(),
+        signer: signer.key(),
+        timestamp: Clock::get()?.unix_timestamp as u64,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,35 @@
+use {
+    crate::{errors::*, events::*, state::*},
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+#[instruction(new_admin: Pubkey)]
+pub struct TransferAdmin<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [SEED_CONFIG],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::InvalidAdmin,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+pub fn handler(ctx: Context<TransferAdmin>, new_admin: Pubkey) -> ProgramResult {
+    // Get accounts.
+    let admin = &ctx.accounts.admin;
+    let config = &mut ctx.accounts.config;
+
+    // Nominate the new admin. The transfer completes when they accept it.
+    config.pending_admin = Some(new_admin);
+
+    emit!(AdminTransferProposed {
+        admin: admin.key(),
+        pending_admin: new_admin,
+        timestamp: Clock::get()?.unix_timestamp as u64,
+    });
+
+    Ok(())
+}
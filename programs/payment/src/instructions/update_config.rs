@@ -0,0 +1,72 @@
+use {
+    crate::{errors::*, events::*, schedule::Fees, state::*},
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+#[instruction(
+    transfer_fee_distributor: u64,
+    transfer_fee_program: u64,
+    program_fee_bps: u16,
+    program_fee_min: u64,
+    program_fee_max: u64,
+    time_granularity: u64,
+)]
+pub struct UpdateConfig<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [SEED_CONFIG],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::InvalidAdmin,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+pub fn handler(
+    ctx: Context<UpdateConfig>,
+    transfer_fee_distributor: u64,
+    transfer_fee_program: u64,
+    program_fee_bps: u16,
+    program_fee_min: u64,
+    program_fee_max: u64,
+    time_granularity: u64,
+) -> ProgramResult {
+    // Get accounts.
+    let config = &mut ctx.accounts.config;
+
+    // Validate the fees.
+    Fees {
+        transfer_fee_distributor,
+        transfer_fee_program,
+        program_fee_bps,
+        program_fee_min,
+        program_fee_max,
+    }
+    .validate()?;
+
+    // Validate the time granularity.
+    require!(time_granularity > 0, ErrorCode::InvalidTimeGranularity);
+
+    // Save config data. Existing payments keep the fees and time granularity they were created
+    // with.
+    config.transfer_fee_distributor = transfer_fee_distributor;
+    config.transfer_fee_program = transfer_fee_program;
+    config.program_fee_bps = program_fee_bps;
+    config.program_fee_min = program_fee_min;
+    config.program_fee_max = program_fee_max;
+    config.time_granularity = time_granularity;
+
+    emit!(ConfigUpdated {
+        transfer_fee_distributor,
+        transfer_fee_program,
+        program_fee_bps,
+        program_fee_min,
+        program_fee_max,
+        time_granularity,
+        timestamp: Clock::get()?.unix_timestamp as u64,
+    });
+
+    Ok(())
+}
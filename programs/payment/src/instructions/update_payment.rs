@@ -0,0 +1,168 @@
+use {
+    super::utils::*,
+    crate::{
+        errors::*,
+        events::*,
+        schedule::{lowers_amount, AmountRule, RecurrenceRule, Schedule},
+        state::*,
+    },
+    anchor_lang::{prelude::*, solana_program::system_program},
+    anchor_spl::token::{Token, TokenAccount},
+};
+
+#[derive(Accounts)]
+#[instruction(
+    memo: String,
+    amount: u64,
+    amount_rule: Option<AmountRule>,
+    recurrence_interval: u64,
+    recurrence_rule: Option<RecurrenceRule>,
+    end_at: u64,
+)]
+pub struct UpdatePayment<'info> {
+    pub creditor: AccountInfo<'info>,
+
+    #[account(
+        constraint = creditor_tokens.owner == creditor.key(),
+        constraint = creditor_tokens.mint == payment.mint
+    )]
+    pub creditor_tokens: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub debtor: Signer<'info>,
+
+    #[account(mut)]
+    pub debtor_tokens: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        has_one = creditor,
+        has_one = debtor,
+        has_one = debtor_tokens,
+        constraint = payment.status == PaymentStatus::Active @ ErrorCode::InvalidPaymentStatus,
+    )]
+    pub payment: Box<Account<'info, Payment>>,
+
+    #[account(address = system_program::ID)]
+    pub system_program: Program<'info, System>,
+
+    #[account(
+        has_one = payment,
+        constraint = task.status == TaskStatus::Pending @ ErrorCode::InvalidTaskStatus,
+    )]
+    pub task: Account<'info, Task>,
+
+    #[account(address = anchor_spl::token::ID)]
+    pub token_program: Program<'info, Token>,
+
+    #[account(
+        mut,
+        seeds = [
+            SEED_VAULT,
+            debtor.key().as_ref(),
+            debtor_tokens.key().as_ref(),
+        ],
+        bump = vault.bump,
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+}
+
+pub fn handler(
+    ctx: Context<UpdatePayment>,
+    memo: String,
+    amount: u64,
+    amount_rule: Option<AmountRule>,
+    recurrence_interval: u64,
+    recurrence_rule: Option<RecurrenceRule>,
+    end_at: u64,
+) -> ProgramResult {
+    // Get accounts.
+    let creditor = &ctx.accounts.creditor;
+    let creditor_tokens = &ctx.accounts.creditor_tokens;
+    let debtor = &ctx.accounts.debtor;
+    let debtor_tokens = &ctx.accounts.debtor_tokens;
+    let payment = &mut ctx.accounts.payment;
+    let system_program = &ctx.accounts.system_program;
+    let task = &ctx.accounts.task;
+    let token_program = &ctx.accounts.token_program;
+    let vault = &mut ctx.accounts.vault;
+
+    // Validate the new schedule is well-formed and still includes the pending task.
+    let schedule =
+        Schedule::new(task.occurrence_at, end_at, recurrence_interval).with_rule(recurrence_rule);
+    schedule.validate_recurrence(payment.time_granularity)?;
+    if let Some(amount_rule) = amount_rule {
+        amount_rule.validate()?;
+    }
+
+    // Calculate the number of transfers remaining, starting from the pending task.
+    let num_transfers = schedule.num_transfers();
+    let prev_num_transfers = payment.schedule().num_transfers_from(task.occurrence_at);
+
+    // Validate the creditor approved any change that affects them: fewer transfers, a lower
+    // amount on any remaining transfer, a later date for any remaining transfer, or a different
+    // destination for their tokens.
+    require!(
+        creditor.is_signer
+            || (num_transfers >= prev_num_transfers
+                && !lowers_amount(
+                    amount,
+                    amount_rule,
+                    payment.amount,
+                    payment.amount_rule,
+                    task.occurrence_index,
+                    prev_num_transfers,
+                )
+                && !schedule.defers(&payment.schedule(), task.occurrence_at)
+                && creditor_tokens.key() == payment.creditor_tokens),
+        ErrorCode::CreditorSignatureRequired
+    );
+
+    // Save payment data.
+    payment.memo = memo;
+    payment.creditor_tokens = creditor_tokens.key();
+    payment.amount = amount;
+    payment.amount_rule = amount_rule;
+    payment.recurrence_interval = recurrence_interval;
+    payment.recurrence_rule = recurrence_rule;
+    payment.end_at = end_at;
+
+    // Reserve the payment's tokens for the remaining transfers in the debtor's vault, in place
+    // of its previous reservation.
+    let delegated_amount =
+        payment
+            .fees()
+            .total_tokens(amount, amount_rule, task.occurrence_index, num_transfers);
+    reserve(payment, vault, delegated_amount);
+
+    // Authorize the vault to transfer the tokens reserved by all of the debtor's payments.
+    approve_vault(
+        token_program,
+        debtor.to_account_info(),
+        debtor_tokens,
+        vault,
+    )?;
+
+    // Settle the difference between the escrowed and the required transfer fees, at the rate
+    // recorded when the payment was created.
+    let transfer_fee = payment.fees().total_lamports(num_transfers)?;
+    settle_escrow(
+        payment.to_account_info(),
+        debtor.to_account_info(),
+        system_program,
+        transfer_fee,
+    )?;
+
+    emit!(PaymentUpdated {
+        payment: payment.key(),
+        creditor_tokens: creditor_tokens.key(),
+        amount,
+        amount_rule,
+        recurrence_interval,
+        recurrence_rule,
+        end_at,
+        timestamp: Clock::get()?.unix_timestamp as u64,
+    });
+
+    Ok(())
+}
@@ -1,16 +1,379 @@
-use anchor_lang::prelude::Pubkey;
-
-use crate::state::Role;
+use {
+    crate::{errors::ErrorCode, events::*, schedule::MAX_SPLITS, state::*},
+    anchor_lang::{
+        prelude::*,
+        solana_program::{program::invoke, system_instruction},
+    },
+    anchor_spl::token::{approve, transfer, Approve, Token, TokenAccount, Transfer},
+    index_program::{
+        cpi::{accounts::CreatePointer, create_pointer},
+        program::IndexProgram,
+        state::Index,
+    },
+};
 
 pub const ONE_MINUTE: u64 = 60;
 
 pub fn _payment_index_namespace(party: Pubkey, role: Role) -> String {
     match role {
-        Role::Creditor => format!("cp_{}", party.to_string()),
-        Role::Debtor => format!("dp_{}", party.to_string()),
+        Role::Creditor => format!("cp_{}", party),
+        Role::Debtor => format!("dp_{}", party),
     }
 }
 
-pub fn _task_index_namespace(process_at: u64) -> String {
-    format!("faktor.tasks.{}", process_at)
+/// Validates retries are scheduled in units of the time granularity, and within the grace period
+/// so that a retry is not expired before it is attempted.
+pub fn validate_retries(
+    time_granularity: u64,
+    grace_period: u64,
+    retry_interval: u64,
+    max_retries: u8,
+) -> ProgramResult {
+    require!(
+        max_retries == 0
+            || (retry_interval > 0
+                && retry_interval.checked_rem(time_granularity) == Some(0)
+                && (grace_period == 0 || retry_interval < grace_period)),
+        ErrorCode::InvalidRetryInterval
+    );
+    Ok(())
+}
+
+/// Initializes an active payment with the program's current fees and time granularity. Every
+/// mode-specific field is cleared, for the caller to set.
+pub fn init_payment(
+    payment: &mut Payment,
+    config: &Config,
+    id: String,
+    memo: String,
+    debtor: Pubkey,
+    debtor_tokens: Pubkey,
+    creditor: Pubkey,
+    creditor_tokens: Pubkey,
+    mint: Pubkey,
+    mode: PaymentMode,
+    start_at: u64,
+    end_at: u64,
+    bump: u8,
+) {
+    payment.id = id;
+    payment.memo = memo;
+    payment.debtor = debtor;
+    payment.debtor_tokens = debtor_tokens;
+    payment.creditor = creditor;
+    payment.creditor_tokens = creditor_tokens;
+    payment.mint = mint;
+    payment.mode = mode;
+    payment.amount = 0;
+    payment.amount_rule = None;
+    payment.recurrence_interval = 0;
+    payment.recurrence_rule = None;
+    payment.start_at = start_at;
+    payment.end_at = end_at;
+    payment.grace_period = 0;
+    payment.time_granularity = config.time_granularity;
+    payment.transfer_fee_distributor = config.transfer_fee_distributor;
+    payment.transfer_fee_program = config.transfer_fee_program;
+    payment.program_fee_bps = config.program_fee_bps;
+    payment.program_fee_min = config.program_fee_min;
+    payment.program_fee_max = config.program_fee_max;
+    payment.status = PaymentStatus::Active;
+    payment.paused_at = 0;
+    payment.resume_policy = ResumePolicy::Continue;
+    payment.retry_interval = 0;
+    payment.max_retries = 0;
+    payment.max_failures = 0;
+    payment.failure_count = 0;
+    payment.last_failure_at = 0;
+    payment.last_failure_reason = FailureReason::None;
+    payment.rate = 0;
+    payment.claimed_until = 0;
+    payment.claimed_amount = 0;
+    payment.period_started_at = 0;
+    payment.period_spent = 0;
+    payment.unit_price = 0;
+    payment.usage = 0;
+    payment.delegated_amount = 0;
+    payment.splits = None;
+    payment.split_tokens = [Pubkey::default(); MAX_SPLITS];
+    payment.bump = bump;
+}
+
+/// Creates a pointer to the given account at the next position in one of the program's indexes.
+pub fn index_account<'info>(
+    authority: &Account<'info, Authority>,
+    index_program: &Program<'info, IndexProgram>,
+    index: &Account<'info, Index>,
+    pointer: &AccountInfo<'info>,
+    proof: &AccountInfo<'info>,
+    payer: AccountInfo<'info>,
+    system_program: &Program<'info, System>,
+    account: Pubkey,
+    pointer_bump: u8,
+    proof_bump: u8,
+) -> ProgramResult {
+    create_pointer(
+        CpiContext::new_with_signer(
+            index_program.to_account_info(),
+            CreatePointer {
+                index: index.to_account_info(),
+                pointer: pointer.clone(),
+                proof: proof.clone(),
+                owner: authority.to_account_info(),
+                payer,
+                system_program: system_program.to_account_info(),
+            },
+            &[&[SEED_AUTHORITY, &[authority.bump]]],
+        ),
+        index.count.to_string(),
+        account,
+        pointer_bump,
+        proof_bump,
+    )
+}
+
+/// Replaces the payment's reservation in the debtor's vault with the given amount of tokens.
+pub fn reserve(payment: &mut Payment, vault: &mut Vault, delegated_amount: u64) {
+    vault.delegated_amount = vault
+        .delegated_amount
+        .saturating_sub(payment.delegated_amount)
+        .saturating_add(delegated_amount);
+    payment.delegated_amount = delegated_amount;
+}
+
+/// Releases the given amount of tokens from the payment's reservation in the debtor's vault.
+pub fn release(payment: &mut Payment, vault: &mut Vault, amount: u64) {
+    let delegated_amount = payment.delegated_amount.saturating_sub(amount);
+    reserve(payment, vault, delegated_amount);
+}
+
+/// Authorizes the vault to transfer the tokens reserved by all of the debtor's payments.
+pub fn approve_vault<'info>(
+    token_program: &Program<'info, Token>,
+    debtor: AccountInfo<'info>,
+    debtor_tokens: &Account<'info, TokenAccount>,
+    vault: &Account<'info, Vault>,
+) -> ProgramResult {
+    approve(
+        CpiContext::new(
+            token_program.to_account_info(),
+            Approve {
+                authority: debtor,
+                delegate: vault.to_account_info(),
+                to: debtor_tokens.to_account_info(),
+            },
+        ),
+        vault.delegated_amount,
+    )
+}
+
+/// Transfers a token program fee from the debtor's tokens to the treasury's token account for the
+/// payment's mint, signed by the debtor's vault.
+pub fn pay_token_fee<'info>(
+    token_program: &Program<'info, Token>,
+    treasury: &Account<'info, Treasury>,
+    treasury_tokens: &AccountInfo<'info>,
+    debtor_tokens: &Account<'info, TokenAccount>,
+    vault: &Account<'info, Vault>,
+    mint: Pubkey,
+    token_fee: u64,
+) -> ProgramResult {
+    if token_fee == 0 {
+        return Ok(());
+    }
+    let treasury_tokens_data = Account::<TokenAccount>::try_from(treasury_tokens)?;
+    require!(
+        treasury_tokens_data.owner == treasury.key() && treasury_tokens_data.mint == mint,
+        ErrorCode::InvalidTreasuryTokens
+    );
+    transfer(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            Transfer {
+                authority: vault.to_account_info(),
+                from: debtor_tokens.to_account_info(),
+                to: treasury_tokens.clone(),
+            },
+            &[&[
+                SEED_VAULT,
+                vault.debtor.as_ref(),
+                vault.debtor_tokens.as_ref(),
+                &[vault.bump],
+            ]],
+        ),
+        token_fee,
+    )
+}
+
+/// Settles the lamports the payment holds in escrow for its transfer fees to the given amount,
+/// collecting the shortfall from the debtor or refunding the surplus to them.
+pub fn settle_escrow<'info>(
+    payment: AccountInfo<'info>,
+    debtor: AccountInfo<'info>,
+    system_program: &Program<'info, System>,
+    transfer_fee: u64,
+) -> ProgramResult {
+    let escrow = payment
+        .lamports()
+        .saturating_sub(Rent::get()?.minimum_balance(payment.data_len()));
+    if transfer_fee > escrow {
+        // Collect the shortfall from the debtor.
+        require!(
+            debtor.lamports() >= transfer_fee - escrow,
+            ErrorCode::InsufficientBalance
+        );
+        invoke(
+            &system_instruction::transfer(debtor.key, payment.key, transfer_fee - escrow),
+            &[debtor, payment, system_program.to_account_info()],
+        )?;
+    } else {
+        // Refund the surplus to the debtor.
+        **payment.try_borrow_mut_lamports()? -= escrow - transfer_fee;
+        **debtor.try_borrow_mut_lamports()? += escrow - transfer_fee;
+    }
+    Ok(())
+}
+
+/// Schedules the first task of a newly initialized scheduled payment. Reserves the payment's
+/// tokens in the debtor's vault, collects its transfer fees from the debtor, and indexes the
+/// payment for both parties and the task by its process time.
+pub fn schedule_payment<'info>(
+    authority: &Account<'info, Authority>,
+    creditor_payment_index: &Account<'info, Index>,
+    creditor_payment_pointer: &AccountInfo<'info>,
+    creditor_payment_proof: &AccountInfo<'info>,
+    debtor: &Signer<'info>,
+    debtor_payment_index: &Account<'info, Index>,
+    debtor_payment_pointer: &AccountInfo<'info>,
+    debtor_payment_proof: &AccountInfo<'info>,
+    debtor_tokens: &Account<'info, TokenAccount>,
+    index_program: &Program<'info, IndexProgram>,
+    payment: &mut Account<'info, Payment>,
+    system_program: &Program<'info, System>,
+    task: &mut Account<'info, Task>,
+    task_index: &Account<'info, Index>,
+    task_pointer: &AccountInfo<'info>,
+    task_proof: &AccountInfo<'info>,
+    token_program: &Program<'info, Token>,
+    vault: &mut Account<'info, Vault>,
+    creditor_payment_pointer_bump: u8,
+    creditor_payment_proof_bump: u8,
+    debtor_payment_pointer_bump: u8,
+    debtor_payment_proof_bump: u8,
+    task_bump: u8,
+    task_pointer_bump: u8,
+    task_proof_bump: u8,
+    now: u64,
+) -> ProgramResult {
+    // Calculate expected number of transfers and their fees.
+    let fees = payment.fees();
+    let num_transfers = payment.schedule().num_transfers();
+    let transfer_fee = fees.total_lamports(num_transfers)?;
+
+    // Validate debtor has sufficient lamports to cover transfer fee.
+    require!(
+        debtor.lamports() >= transfer_fee,
+        ErrorCode::InsufficientBalance
+    );
+
+    // Save task data.
+    task.id = task_index.count.to_string();
+    task.payment = payment.key();
+    task.payer = debtor.key();
+    task.process_at = payment.start_at;
+    task.occurrence_at = payment.start_at;
+    task.occurrence_index = 0;
+    task.attempt = 0;
+    task.status = TaskStatus::Pending;
+    task.processed_at = 0;
+    task.processed_slot = 0;
+    task.bump = task_bump;
+
+    // Reserve the payment's tokens, including the token program fees, in the debtor's vault.
+    let delegated_amount = fees.total_tokens(payment.amount, payment.amount_rule, 0, num_transfers);
+    reserve(payment, vault, delegated_amount);
+
+    // Authorize the vault to transfer the tokens reserved by all of the debtor's payments.
+    approve_vault(
+        token_program,
+        debtor.to_account_info(),
+        debtor_tokens,
+        vault,
+    )?;
+
+    // Collect transfer fee from debtor. Hold funds in payment account.
+    invoke(
+        &system_instruction::transfer(&debtor.key(), &payment.key(), transfer_fee),
+        &[
+            debtor.to_account_info(),
+            payment.to_account_info(),
+            system_program.to_account_info(),
+        ],
+    )?;
+
+    // Create pointer to payment in creditor's payment index.
+    index_account(
+        authority,
+        index_program,
+        creditor_payment_index,
+        creditor_payment_pointer,
+        creditor_payment_proof,
+        debtor.to_account_info(),
+        system_program,
+        payment.key(),
+        creditor_payment_pointer_bump,
+        creditor_payment_proof_bump,
+    )?;
+
+    // Create pointer to payment in debtor's payment index.
+    index_account(
+        authority,
+        index_program,
+        debtor_payment_index,
+        debtor_payment_pointer,
+        debtor_payment_proof,
+        debtor.to_account_info(),
+        system_program,
+        payment.key(),
+        debtor_payment_pointer_bump,
+        debtor_payment_proof_bump,
+    )?;
+
+    // Create pointer to task in time-bound task index.
+    index_account(
+        authority,
+        index_program,
+        task_index,
+        task_pointer,
+        task_proof,
+        debtor.to_account_info(),
+        system_program,
+        task.key(),
+        task_pointer_bump,
+        task_proof_bump,
+    )?;
+
+    emit!(PaymentCreated {
+        payment: payment.key(),
+        debtor: payment.debtor,
+        creditor: payment.creditor,
+        mint: payment.mint,
+        amount: payment.amount,
+        amount_rule: payment.amount_rule,
+        recurrence_interval: payment.recurrence_interval,
+        recurrence_rule: payment.recurrence_rule,
+        start_at: payment.start_at,
+        end_at: payment.end_at,
+        timestamp: now,
+    });
+    emit!(TaskScheduled {
+        task: task.key(),
+        payment: payment.key(),
+        task_index: task_index.key(),
+        process_at: task.process_at,
+        attempt: 0,
+        timestamp: now,
+    });
+
+    Ok(())
 }
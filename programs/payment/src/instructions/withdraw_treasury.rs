@@ -0,0 +1,49 @@
+use {
+    crate::{errors::*, events::*, state::*},
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+#[instruction(amount: u64)]
+pub struct WithdrawTreasury<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [SEED_CONFIG],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::InvalidAdmin,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(mut)]
+    pub destination: AccountInfo<'info>,
+
+    #[account(mut, seeds = [SEED_TREASURY], bump = treasury.bump)]
+    pub treasury: Account<'info, Treasury>,
+}
+
+pub fn handler(ctx: Context<WithdrawTreasury>, amount: u64) -> ProgramResult {
+    // Get accounts.
+    let destination = &ctx.accounts.destination;
+    let treasury = &mut ctx.accounts.treasury;
+
+    // Validate the withdrawal leaves the treasury rent-exempt.
+    let treasury_info = treasury.to_account_info();
+    let available = treasury_info
+        .lamports()
+        .saturating_sub(Rent::get()?.minimum_balance(treasury_info.data_len()));
+    require!(amount <= available, ErrorCode::InsufficientTreasuryBalance);
+
+    // Transfer lamports from the treasury to the destination.
+    **treasury_info.try_borrow_mut_lamports()? -= amount;
+    **destination.try_borrow_mut_lamports()? += amount;
+    treasury.total_withdrawn += amount;
+
+    emit!(TreasuryWithdrawn {
+        destination: destination.key(),
+        amount,
+        timestamp: Clock::get()?.unix_timestamp as u64,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,64 @@
+use {
+    crate::{errors::*, events::*, state::*},
+    anchor_lang::prelude::*,
+    anchor_spl::token::{transfer, Token, TokenAccount, Transfer},
+};
+
+#[derive(Accounts)]
+#[instruction(amount: u64)]
+pub struct WithdrawTreasuryTokens<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [SEED_CONFIG],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::InvalidAdmin,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(mut)]
+    pub destination_tokens: Account<'info, TokenAccount>,
+
+    #[account(address = anchor_spl::token::ID)]
+    pub token_program: Program<'info, Token>,
+
+    #[account(seeds = [SEED_TREASURY], bump = treasury.bump)]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        mut,
+        constraint = treasury_tokens.owner == treasury.key() @ ErrorCode::InvalidTreasuryTokens,
+    )]
+    pub treasury_tokens: Account<'info, TokenAccount>,
+}
+
+pub fn handler(ctx: Context<WithdrawTreasuryTokens>, amount: u64) -> ProgramResult {
+    // Get accounts.
+    let destination_tokens = &ctx.accounts.destination_tokens;
+    let token_program = &ctx.accounts.token_program;
+    let treasury = &ctx.accounts.treasury;
+    let treasury_tokens = &ctx.accounts.treasury_tokens;
+
+    // Transfer token fees from the treasury to the destination.
+    transfer(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            Transfer {
+                authority: treasury.to_account_info(),
+                from: treasury_tokens.to_account_info(),
+                to: destination_tokens.to_account_info(),
+            },
+            &[&[SEED_TREASURY, &[treasury.bump]]],
+        ),
+        amount,
+    )?;
+
+    emit!(TreasuryTokensWithdrawn {
+        destination_tokens: destination_tokens.key(),
+        mint: treasury_tokens.mint,
+        amount,
+        timestamp: Clock::get()?.unix_timestamp as u64,
+    });
+
+    Ok(())
+}
@@ -1,8 +1,17 @@
+#![allow(clippy::too_many_arguments)]
+
 pub mod errors;
+pub mod events;
 mod instructions;
+pub mod schedule;
 pub mod state;
 
-use {anchor_lang::prelude::*, instructions::*};
+use {
+    anchor_lang::prelude::*,
+    instructions::*,
+    schedule::{AmountRule, RecurrenceRule},
+    state::*,
+};
 
 declare_id!("3uvTgoiGSBz6ntktxo3gwTJY3wDfG73LGNc21AHYiJg2");
 
@@ -28,13 +37,132 @@ pub mod payment_program {
         )
     }
 
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> ProgramResult {
+        accept_admin::handler(ctx)
+    }
+
+    pub fn accept_invoice(
+        ctx: Context<AcceptInvoice>,
+        creditor_payment_pointer_bump: u8,
+        creditor_payment_proof_bump: u8,
+        debtor_payment_pointer_bump: u8,
+        debtor_payment_proof_bump: u8,
+        payment_bump: u8,
+        task_bump: u8,
+        task_pointer_bump: u8,
+        task_proof_bump: u8,
+    ) -> ProgramResult {
+        accept_invoice::handler(
+            ctx,
+            creditor_payment_pointer_bump,
+            creditor_payment_proof_bump,
+            debtor_payment_pointer_bump,
+            debtor_payment_proof_bump,
+            payment_bump,
+            task_bump,
+            task_pointer_bump,
+            task_proof_bump,
+        )
+    }
+
+    pub fn cancel_payment<'info>(
+        ctx: Context<'_, '_, '_, 'info, CancelPayment<'info>>,
+    ) -> ProgramResult {
+        cancel_payment::handler(ctx)
+    }
+
+    pub fn charge(ctx: Context<Charge>, amount: u64) -> ProgramResult {
+        charge::handler(ctx, amount)
+    }
+
+    pub fn claim(ctx: Context<Claim>) -> ProgramResult {
+        claim::handler(ctx)
+    }
+
+    pub fn close_invoice(ctx: Context<CloseInvoice>) -> ProgramResult {
+        close_invoice::handler(ctx)
+    }
+
+    pub fn create_allowance(
+        ctx: Context<CreateAllowance>,
+        memo: String,
+        amount: u64,
+        period: u64,
+        start_at: u64,
+        end_at: u64,
+        creditor_payment_pointer_bump: u8,
+        creditor_payment_proof_bump: u8,
+        debtor_payment_pointer_bump: u8,
+        debtor_payment_proof_bump: u8,
+        payment_bump: u8,
+    ) -> ProgramResult {
+        create_allowance::handler(
+            ctx,
+            memo,
+            amount,
+            period,
+            start_at,
+            end_at,
+            creditor_payment_pointer_bump,
+            creditor_payment_proof_bump,
+            debtor_payment_pointer_bump,
+            debtor_payment_proof_bump,
+            payment_bump,
+        )
+    }
+
+    pub fn create_invoice(
+        ctx: Context<CreateInvoice>,
+        id: u64,
+        memo: String,
+        amount: u64,
+        amount_rule: Option<AmountRule>,
+        recurrence_interval: u64,
+        recurrence_rule: Option<RecurrenceRule>,
+        start_at: u64,
+        end_at: u64,
+        grace_period: u64,
+        retry_interval: u64,
+        max_retries: u8,
+        max_failures: u8,
+        expires_at: u64,
+        bump: u8,
+    ) -> ProgramResult {
+        create_invoice::handler(
+            ctx,
+            id,
+            memo,
+            amount,
+            amount_rule,
+            recurrence_interval,
+            recurrence_rule,
+            start_at,
+            end_at,
+            grace_period,
+            retry_interval,
+            max_retries,
+            max_failures,
+            expires_at,
+            bump,
+        )
+    }
+
     pub fn create_payment(
         ctx: Context<CreatePayment>,
         memo: String,
         amount: u64,
+        amount_rule: Option<AmountRule>,
         recurrence_interval: u64,
+        recurrence_rule: Option<RecurrenceRule>,
         start_at: u64,
         end_at: u64,
+        grace_period: u64,
+        retry_interval: u64,
+        max_retries: u8,
+        max_failures: u8,
+        splits: Option<Splits>,
+        mode: PaymentMode,
+        unit_price: u64,
         creditor_payment_pointer_bump: u8,
         creditor_payment_proof_bump: u8,
         debtor_payment_pointer_bump: u8,
@@ -48,9 +176,18 @@ pub mod payment_program {
             ctx,
             memo,
             amount,
+            amount_rule,
             recurrence_interval,
+            recurrence_rule,
             start_at,
             end_at,
+            grace_period,
+            retry_interval,
+            max_retries,
+            max_failures,
+            splits,
+            mode,
+            unit_price,
             creditor_payment_pointer_bump,
             creditor_payment_proof_bump,
             debtor_payment_pointer_bump,
@@ -62,23 +199,138 @@ pub mod payment_program {
         )
     }
 
+    pub fn create_stream(
+        ctx: Context<CreateStream>,
+        memo: String,
+        rate: u64,
+        start_at: u64,
+        end_at: u64,
+        creditor_payment_pointer_bump: u8,
+        creditor_payment_proof_bump: u8,
+        debtor_payment_pointer_bump: u8,
+        debtor_payment_proof_bump: u8,
+        payment_bump: u8,
+    ) -> ProgramResult {
+        create_stream::handler(
+            ctx,
+            memo,
+            rate,
+            start_at,
+            end_at,
+            creditor_payment_pointer_bump,
+            creditor_payment_proof_bump,
+            debtor_payment_pointer_bump,
+            debtor_payment_proof_bump,
+            payment_bump,
+        )
+    }
+
     pub fn create_task_index(
         ctx: Context<CreateTaskIndex>,
         process_at: u64,
-        bump: u8,
+        index_bump: u8,
+        namespace_bump: u8,
     ) -> ProgramResult {
-        create_task_index::handler(ctx, process_at, bump)
+        create_task_index::handler(ctx, process_at, index_bump, namespace_bump)
+    }
+
+    pub fn create_vault(ctx: Context<CreateVault>, bump: u8) -> ProgramResult {
+        create_vault::handler(ctx, bump)
     }
 
     pub fn create_payment_index(
         ctx: Context<CreatePaymentIndex>,
+        role: Role,
         index_bump: u8,
         namespace_bump: u8,
     ) -> ProgramResult {
-        create_payment_index::handler(ctx, index_bump, namespace_bump)
+        create_payment_index::handler(ctx, role, index_bump, namespace_bump)
+    }
+
+    pub fn pause_payment(ctx: Context<PausePayment>, resume_policy: ResumePolicy) -> ProgramResult {
+        pause_payment::handler(ctx, resume_policy)
+    }
+
+    pub fn process_task<'info>(
+        ctx: Context<'_, '_, '_, 'info, ProcessTask<'info>>,
+        next_task_bump: u8,
+        next_task_pointer_bump: u8,
+        next_task_proof_bump: u8,
+    ) -> ProgramResult {
+        process_task::handler(
+            ctx,
+            next_task_bump,
+            next_task_pointer_bump,
+            next_task_proof_bump,
+        )
+    }
+
+    pub fn renew_allowance<'info>(
+        ctx: Context<'_, '_, '_, 'info, RenewAllowance<'info>>,
+    ) -> ProgramResult {
+        renew_allowance::handler(ctx)
+    }
+
+    pub fn report_usage(ctx: Context<ReportUsage>, units: u64) -> ProgramResult {
+        report_usage::handler(ctx, units)
+    }
+
+    pub fn resume_payment(ctx: Context<ResumePayment>) -> ProgramResult {
+        resume_payment::handler(ctx)
+    }
+
+    pub fn transfer_admin(ctx: Context<TransferAdmin>, new_admin: Pubkey) -> ProgramResult {
+        transfer_admin::handler(ctx, new_admin)
+    }
+
+    pub fn update_config(
+        ctx: Context<UpdateConfig>,
+        transfer_fee_distributor: u64,
+        transfer_fee_program: u64,
+        program_fee_bps: u16,
+        program_fee_min: u64,
+        program_fee_max: u64,
+        time_granularity: u64,
+    ) -> ProgramResult {
+        update_config::handler(
+            ctx,
+            transfer_fee_distributor,
+            transfer_fee_program,
+            program_fee_bps,
+            program_fee_min,
+            program_fee_max,
+            time_granularity,
+        )
+    }
+
+    pub fn update_payment(
+        ctx: Context<UpdatePayment>,
+        memo: String,
+        amount: u64,
+        amount_rule: Option<AmountRule>,
+        recurrence_interval: u64,
+        recurrence_rule: Option<RecurrenceRule>,
+        end_at: u64,
+    ) -> ProgramResult {
+        update_payment::handler(
+            ctx,
+            memo,
+            amount,
+            amount_rule,
+            recurrence_interval,
+            recurrence_rule,
+            end_at,
+        )
     }
 
-    pub fn process_task(ctx: Context<ProcessTask>) -> ProgramResult {
-        process_task::handler(ctx)
+    pub fn withdraw_treasury(ctx: Context<WithdrawTreasury>, amount: u64) -> ProgramResult {
+        withdraw_treasury::handler(ctx, amount)
+    }
+
+    pub fn withdraw_treasury_tokens(
+        ctx: Context<WithdrawTreasuryTokens>,
+        amount: u64,
+    ) -> ProgramResult {
+        withdraw_treasury_tokens::handler(ctx, amount)
     }
 }
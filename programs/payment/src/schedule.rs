@@ -0,0 +1,989 @@
+//! Schedule engine shared by the on-chain program and off-chain clients.
+//!
+//! Clients can quote payments with exactly the same arithmetic the program uses to charge them.
+//! The module is `no_std` compatible: it only uses `core`. The rules stored on a payment derive
+//! Anchor's Borsh traits behind the `borsh` feature, which the program enables by default, so
+//! clients can build the module without `anchor_lang` or `std`.
+
+#[cfg(feature = "borsh")]
+use anchor_lang::prelude::{borsh, AnchorDeserialize, AnchorSerialize};
+use core::cmp::{max, min};
+
+pub const MAX_FEE_BPS: u16 = 10_000;
+
+pub const SECONDS_PER_DAY: u64 = 86_400;
+
+pub const MAX_AMOUNT_STEPS: usize = 12;
+
+pub const MAX_RAMP_LENGTH: u16 = 360;
+
+pub const MAX_SPLITS: usize = 8;
+
+/// Upper bound on the occurrences of a schedule with a recurrence rule, which are counted by
+/// walking the calendar. Five years of weekly occurrences.
+pub const MAX_RULE_OCCURRENCES: usize = 260;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScheduleError {
+    /// The timestamps are not chronological.
+    InvalidChronology,
+    /// The time granularity is zero.
+    InvalidTimeGranularity,
+    /// The start is not at the start of a time bucket.
+    InvalidStartAt,
+    /// The recurrence interval is not a whole number of time buckets, or does not fit the window.
+    InvalidRecurrenceInterval,
+    /// The fee is above 10000 basis points or its minimum is above its maximum.
+    InvalidFee,
+    /// The recurrence rule is malformed, does not match the start, is combined with an interval, or
+    /// selects more than `MAX_RULE_OCCURRENCES` occurrences.
+    InvalidRecurrenceRule,
+    /// The amount rule is malformed.
+    InvalidAmountRule,
+    /// The split table is empty, has an empty share, or its shares are above 10000 basis points.
+    InvalidSplits,
+    /// The fees do not fit in a `u64`.
+    Overflow,
+}
+
+/// A rule for payments whose amount varies by occurrence. Occurrences are numbered from zero.
+#[cfg_attr(feature = "borsh", derive(AnchorSerialize, AnchorDeserialize))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AmountRule {
+    /// The amount changes by `step` tokens on each of the first `length` occurrences, then stays.
+    Ramp { step: i64, length: u16 },
+    /// The amount of each occurrence is read from the first `len` entries of `amounts`. The last
+    /// entry repeats once the table runs out.
+    Steps {
+        len: u8,
+        amounts: [u64; MAX_AMOUNT_STEPS],
+    },
+    /// The amount grows by `bps` basis points every `every` occurrences.
+    Escalation { bps: u16, every: u16 },
+}
+
+impl AmountRule {
+    pub fn validate(&self) -> Result<(), ScheduleError> {
+        let is_valid = match *self {
+            AmountRule::Ramp { length, .. } => length <= MAX_RAMP_LENGTH,
+            AmountRule::Steps { len, .. } => len > 0 && len as usize <= MAX_AMOUNT_STEPS,
+            AmountRule::Escalation { every, .. } => every > 0,
+        };
+        match is_valid {
+            true => Ok(()),
+            false => Err(ScheduleError::InvalidAmountRule),
+        }
+    }
+}
+
+/// Returns the amount due on the given occurrence of a payment.
+pub fn amount_at(amount: u64, amount_rule: Option<AmountRule>, occurrence_index: u64) -> u64 {
+    AmountSegments::new(amount, amount_rule, occurrence_index, 1)
+        .next()
+        .map_or(amount, |(amount, _)| amount)
+}
+
+/// Returns the total amount due on `count` occurrences starting from the given one.
+pub fn total_amount(
+    amount: u64,
+    amount_rule: Option<AmountRule>,
+    occurrence_index: u64,
+    count: u64,
+) -> u64 {
+    AmountSegments::new(amount, amount_rule, occurrence_index, count)
+        .map(|(amount, repeat)| amount.saturating_mul(repeat))
+        .fold(0, u64::saturating_add)
+}
+
+/// Returns whether any of `count` occurrences starting from the given one is due less under the
+/// new amount and rule than under the previous ones.
+pub fn lowers_amount(
+    amount: u64,
+    amount_rule: Option<AmountRule>,
+    prev_amount: u64,
+    prev_amount_rule: Option<AmountRule>,
+    occurrence_index: u64,
+    count: u64,
+) -> bool {
+    let mut segments = AmountSegments::new(amount, amount_rule, occurrence_index, count);
+    let mut prev_segments =
+        AmountSegments::new(prev_amount, prev_amount_rule, occurrence_index, count);
+    let mut segment = segments.next();
+    let mut prev_segment = prev_segments.next();
+    while let (Some((amount, repeat)), Some((prev_amount, prev_repeat))) = (segment, prev_segment) {
+        if amount < prev_amount {
+            return true;
+        }
+        // Advance past the occurrences both runs cover.
+        let overlap = min(repeat, prev_repeat);
+        segment = match repeat > overlap {
+            true => Some((amount, repeat - overlap)),
+            false => segments.next(),
+        };
+        prev_segment = match prev_repeat > overlap {
+            true => Some((prev_amount, prev_repeat - overlap)),
+            false => prev_segments.next(),
+        };
+    }
+    false
+}
+
+/// Iterator over runs of consecutive occurrences that share an amount, as `(amount, repeat)`
+/// pairs. Runs keep totals cheap to compute for long schedules.
+#[derive(Clone, Debug)]
+pub struct AmountSegments {
+    amount: u64,
+    amount_rule: Option<AmountRule>,
+    next: u64,
+    end: u64,
+}
+
+impl AmountSegments {
+    pub fn new(
+        amount: u64,
+        amount_rule: Option<AmountRule>,
+        occurrence_index: u64,
+        count: u64,
+    ) -> Self {
+        // Fast-forward escalations to the first occurrence.
+        let mut amount = amount;
+        if let Some(AmountRule::Escalation { bps, every }) = amount_rule {
+            for _ in 0..occurrence_index / every as u64 {
+                amount = escalate(amount, bps);
+            }
+        }
+        AmountSegments {
+            amount,
+            amount_rule,
+            next: occurrence_index,
+            end: occurrence_index.saturating_add(count),
+        }
+    }
+}
+
+impl Iterator for AmountSegments {
+    type Item = (u64, u64);
+
+    fn next(&mut self) -> Option<(u64, u64)> {
+        if self.next >= self.end {
+            return None;
+        }
+        let (amount, repeat) = match self.amount_rule {
+            None => (self.amount, self.end - self.next),
+            Some(AmountRule::Ramp { step, length }) => {
+                let steps = min(self.next, length as u64) as i128;
+                let amount =
+                    (self.amount as i128 + step as i128 * steps).clamp(0, u64::MAX as i128) as u64;
+                match self.next < length as u64 {
+                    true => (amount, 1),
+                    false => (amount, self.end - self.next),
+                }
+            }
+            Some(AmountRule::Steps { len, amounts }) => {
+                let last = len as u64 - 1;
+                match self.next < last {
+                    true => (amounts[self.next as usize], 1),
+                    false => (amounts[last as usize], self.end - self.next),
+                }
+            }
+            Some(AmountRule::Escalation { bps, every }) => {
+                let every = every as u64;
+                let block_end = (self.next / every + 1) * every;
+                let amount = self.amount;
+                if block_end < self.end {
+                    self.amount = escalate(self.amount, bps);
+                }
+                (amount, min(block_end, self.end) - self.next)
+            }
+        };
+        self.next += repeat;
+        Some((amount, repeat))
+    }
+}
+
+fn escalate(amount: u64, bps: u16) -> u64 {
+    let escalated = amount as u128 * (MAX_FEE_BPS as u128 + bps as u128) / MAX_FEE_BPS as u128;
+    min(escalated, u64::MAX as u128) as u64
+}
+
+/// A calendar rule for recurring payments. Occurrences keep the time of day of the schedule's
+/// start and are evaluated in UTC.
+#[cfg_attr(feature = "borsh", derive(AnchorSerialize, AnchorDeserialize))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecurrenceRule {
+    /// Every `interval` months on `day` of the month. Days past the end of a month fall on its
+    /// last day.
+    Monthly { interval: u8, day: u8 },
+    /// Every `interval` months on the last weekday (Monday to Friday) of the month.
+    MonthlyLastBusinessDay { interval: u8 },
+    /// Every `interval` weeks on `weekday`, where 0 is Monday and 6 is Sunday.
+    Weekly { interval: u8, weekday: u8 },
+}
+
+impl RecurrenceRule {
+    pub fn validate(&self) -> Result<(), ScheduleError> {
+        let is_valid = match *self {
+            RecurrenceRule::Monthly { interval, day } => interval > 0 && (1..=31).contains(&day),
+            RecurrenceRule::MonthlyLastBusinessDay { interval } => interval > 0,
+            RecurrenceRule::Weekly { interval, weekday } => interval > 0 && weekday <= 6,
+        };
+        match is_valid {
+            true => Ok(()),
+            false => Err(ScheduleError::InvalidRecurrenceRule),
+        }
+    }
+
+    /// Returns whether the given timestamp falls on a day selected by the rule.
+    pub fn matches(&self, ts: u64) -> bool {
+        let days = (ts / SECONDS_PER_DAY) as i64;
+        let (year, month, day) = civil_from_days(days);
+        match *self {
+            RecurrenceRule::Monthly { day: rule_day, .. } => {
+                day == min(rule_day as u32, days_in_month(year, month))
+            }
+            RecurrenceRule::MonthlyLastBusinessDay { .. } => day == last_business_day(year, month),
+            RecurrenceRule::Weekly { weekday, .. } => weekday_from_days(days) == weekday as u32,
+        }
+    }
+
+    /// Returns the occurrence that follows the given one.
+    pub fn advance(&self, ts: u64) -> Option<u64> {
+        let days = (ts / SECONDS_PER_DAY) as i64;
+        let time_of_day = ts % SECONDS_PER_DAY;
+        let next_days = match *self {
+            RecurrenceRule::Monthly { interval, day } => {
+                let (year, month) = add_months(days, interval);
+                days_from_civil(year, month, min(day as u32, days_in_month(year, month)))
+            }
+            RecurrenceRule::MonthlyLastBusinessDay { interval } => {
+                let (year, month) = add_months(days, interval);
+                days_from_civil(year, month, last_business_day(year, month))
+            }
+            RecurrenceRule::Weekly { interval, .. } => days + 7 * interval as i64,
+        };
+        (next_days as u64)
+            .checked_mul(SECONDS_PER_DAY)?
+            .checked_add(time_of_day)
+    }
+}
+
+/// The occurrences of a one-time (`recurrence_interval == 0`) or recurring payment. Both ends
+/// of the window are inclusive.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Schedule {
+    pub start_at: u64,
+    pub end_at: u64,
+    pub recurrence_interval: u64,
+    pub recurrence_rule: Option<RecurrenceRule>,
+}
+
+impl Schedule {
+    pub fn new(start_at: u64, end_at: u64, recurrence_interval: u64) -> Self {
+        Schedule {
+            start_at,
+            end_at,
+            recurrence_interval,
+            recurrence_rule: None,
+        }
+    }
+
+    pub fn with_rule(mut self, recurrence_rule: Option<RecurrenceRule>) -> Self {
+        self.recurrence_rule = recurrence_rule;
+        self
+    }
+
+    pub fn is_recurring(&self) -> bool {
+        self.recurrence_interval > 0 || self.recurrence_rule.is_some()
+    }
+
+    /// Validates the schedule against the program's time granularity, and that its recurrence
+    /// fits within its window.
+    pub fn validate(&self, time_granularity: u64) -> Result<(), ScheduleError> {
+        self.validate_recurrence(time_granularity)?;
+        match self.advance(self.start_at) {
+            Some(next) if next > self.end_at => Err(match self.recurrence_rule {
+                Some(_) => ScheduleError::InvalidRecurrenceRule,
+                None => ScheduleError::InvalidRecurrenceInterval,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Validates the schedule against the program's time granularity.
+    pub fn validate_recurrence(&self, time_granularity: u64) -> Result<(), ScheduleError> {
+        if time_granularity == 0 {
+            return Err(ScheduleError::InvalidTimeGranularity);
+        }
+        if self.start_at.checked_rem(time_granularity) != Some(0) {
+            return Err(ScheduleError::InvalidStartAt);
+        }
+        match (self.recurrence_interval, self.recurrence_rule) {
+            (0, None) if self.start_at != self.end_at => Err(ScheduleError::InvalidChronology),
+            (0, None) => Ok(()),
+            _ if self.start_at > self.end_at => Err(ScheduleError::InvalidChronology),
+            (0, Some(rule)) => {
+                rule.validate()?;
+                if SECONDS_PER_DAY.checked_rem(time_granularity) != Some(0)
+                    || !rule.matches(self.start_at)
+                    || self.occurrences().nth(MAX_RULE_OCCURRENCES).is_some()
+                {
+                    return Err(ScheduleError::InvalidRecurrenceRule);
+                }
+                Ok(())
+            }
+            (_, Some(_)) => Err(ScheduleError::InvalidRecurrenceRule),
+            (interval, None) if interval.checked_rem(time_granularity) != Some(0) => {
+                Err(ScheduleError::InvalidRecurrenceInterval)
+            }
+            (_, None) => Ok(()),
+        }
+    }
+
+    /// Returns the occurrence that follows the given one, regardless of the window's end.
+    pub fn advance(&self, occurrence_at: u64) -> Option<u64> {
+        match (self.recurrence_interval, self.recurrence_rule) {
+            (_, Some(rule)) => rule.advance(occurrence_at),
+            (0, None) => None,
+            (interval, None) => occurrence_at.checked_add(interval),
+        }
+    }
+
+    /// Returns the occurrence that follows the given one, if it falls within the window.
+    pub fn next_occurrence(&self, occurrence_at: u64) -> Option<u64> {
+        self.advance(occurrence_at)
+            .filter(|next| *next <= self.end_at)
+    }
+
+    /// Returns the end of a window that admits exactly one more occurrence than this one,
+    /// counting occurrences from the given one onwards.
+    pub fn extended_end_at(&self, occurrence_at: u64) -> u64 {
+        let last = match (self.recurrence_interval, self.recurrence_rule) {
+            (interval, None) if interval > 0 && occurrence_at <= self.end_at => {
+                Some(occurrence_at + (self.num_transfers_from(occurrence_at) - 1) * interval)
+            }
+            _ => self.occurrences_from(occurrence_at).last(),
+        };
+        last.and_then(|last| self.advance(last))
+            .unwrap_or(self.end_at)
+    }
+
+    /// Returns whether any of this schedule's occurrences from the given one onwards falls later
+    /// than the matching occurrence of `prev`. Occurrences are matched in order.
+    pub fn defers(&self, prev: &Schedule, occurrence_at: u64) -> bool {
+        match (self.recurrence_rule, prev.recurrence_rule) {
+            (None, None) => {
+                self.recurrence_interval > prev.recurrence_interval
+                    && self.num_transfers_from(occurrence_at) > 1
+                    && prev.num_transfers_from(occurrence_at) > 1
+            }
+            _ => self
+                .occurrences_from(occurrence_at)
+                .zip(prev.occurrences_from(occurrence_at))
+                .any(|(next, prev)| next > prev),
+        }
+    }
+
+    /// Enumerates every occurrence of the schedule.
+    pub fn occurrences(&self) -> Occurrences {
+        self.occurrences_from(self.start_at)
+    }
+
+    /// Enumerates the occurrences of the schedule from the given occurrence onwards.
+    pub fn occurrences_from(&self, occurrence_at: u64) -> Occurrences {
+        Occurrences {
+            schedule: *self,
+            next: match occurrence_at <= self.end_at {
+                true => Some(occurrence_at),
+                false => None,
+            },
+        }
+    }
+
+    /// Counts every transfer of the schedule.
+    pub fn num_transfers(&self) -> u64 {
+        self.num_transfers_from(self.start_at)
+    }
+
+    /// Counts the transfers of the schedule from the given occurrence onwards.
+    pub fn num_transfers_from(&self, occurrence_at: u64) -> u64 {
+        match (self.recurrence_interval, self.recurrence_rule) {
+            _ if occurrence_at > self.end_at => 0,
+            (_, Some(_)) => self.occurrences_from(occurrence_at).count() as u64,
+            (0, None) => 1,
+            (interval, None) => (self.end_at - occurrence_at) / interval + 1,
+        }
+    }
+}
+
+/// Iterator over the occurrence timestamps of a schedule.
+#[derive(Clone, Debug)]
+pub struct Occurrences {
+    schedule: Schedule,
+    next: Option<u64>,
+}
+
+impl Iterator for Occurrences {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        let occurrence_at = self.next?;
+        self.next = self.schedule.next_occurrence(occurrence_at);
+        Some(occurrence_at)
+    }
+}
+
+/// Validates the shares of each transfer paid to a payment's split recipients, in basis points.
+pub fn validate_splits(bps: &[u16]) -> Result<(), ScheduleError> {
+    let is_valid = !bps.is_empty()
+        && bps.len() <= MAX_SPLITS
+        && bps.iter().all(|&bps| bps > 0)
+        && bps.iter().map(|&bps| bps as u32).sum::<u32>() <= MAX_FEE_BPS as u32;
+    match is_valid {
+        true => Ok(()),
+        false => Err(ScheduleError::InvalidSplits),
+    }
+}
+
+/// Returns the amount paid to each split recipient out of a transfer of the given amount.
+pub fn split_shares(bps: &[u16], amount: u64) -> impl Iterator<Item = u64> + '_ {
+    bps.iter()
+        .map(move |&bps| (amount as u128 * bps as u128 / MAX_FEE_BPS as u128) as u64)
+}
+
+/// Returns the amount paid to the creditor out of a transfer of the given amount. The creditor
+/// receives the rest, including any rounding remainder.
+pub fn split_remainder(bps: &[u16], amount: u64) -> u64 {
+    amount - split_shares(bps, amount).sum::<u64>()
+}
+
+/// The fees charged on each transfer of a payment.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Fees {
+    /// Lamports paid to the distributor who processes a transfer.
+    pub transfer_fee_distributor: u64,
+    /// Lamports paid to the treasury for a transfer.
+    pub transfer_fee_program: u64,
+    /// Tokens paid to the treasury for a transfer, in basis points of the transfer amount.
+    pub program_fee_bps: u16,
+    /// Lower bound on the token fee of a transfer.
+    pub program_fee_min: u64,
+    /// Upper bound on the token fee of a transfer. Zero means uncapped.
+    pub program_fee_max: u64,
+}
+
+impl Fees {
+    pub fn validate(&self) -> Result<(), ScheduleError> {
+        if self.program_fee_bps > MAX_FEE_BPS
+            || (self.program_fee_max > 0 && self.program_fee_min > self.program_fee_max)
+        {
+            return Err(ScheduleError::InvalidFee);
+        }
+        Ok(())
+    }
+
+    /// Returns the lamports prepaid for each transfer.
+    pub fn lamports_per_transfer(&self) -> Result<u64, ScheduleError> {
+        self.transfer_fee_distributor
+            .checked_add(self.transfer_fee_program)
+            .ok_or(ScheduleError::Overflow)
+    }
+
+    /// Returns the token fee charged on a transfer of the given amount.
+    pub fn token_fee(&self, amount: u64) -> u64 {
+        if self.program_fee_bps == 0 {
+            return 0;
+        }
+        let fee = (amount as u128 * self.program_fee_bps as u128 / MAX_FEE_BPS as u128) as u64;
+        let fee = max(fee, self.program_fee_min);
+        match self.program_fee_max {
+            0 => fee,
+            program_fee_max => min(fee, program_fee_max),
+        }
+    }
+
+    /// Returns the lamports prepaid for the given number of transfers.
+    pub fn total_lamports(&self, num_transfers: u64) -> Result<u64, ScheduleError> {
+        num_transfers
+            .checked_mul(self.lamports_per_transfer()?)
+            .ok_or(ScheduleError::Overflow)
+    }
+
+    /// Returns the token fee charged on a transfer of the given amount out of a budget that has
+    /// already transferred `spent` tokens, such as a stream's claims or an allowance period's
+    /// charges. Fees are charged on the cumulative amount, so that the fees of many small
+    /// transfers add up to the fee of one transfer of their total.
+    pub fn cumulative_token_fee(&self, spent: u64, amount: u64) -> Result<u64, ScheduleError> {
+        let cumulative_fee = |spent: u64| match spent {
+            0 => 0,
+            _ => self.token_fee(spent),
+        };
+        let total = spent.checked_add(amount).ok_or(ScheduleError::Overflow)?;
+        Ok(cumulative_fee(total) - cumulative_fee(spent))
+    }
+
+    /// Returns the tokens, including token fees, needed for `num_transfers` transfers starting
+    /// from the given occurrence.
+    pub fn total_tokens(
+        &self,
+        amount: u64,
+        amount_rule: Option<AmountRule>,
+        occurrence_index: u64,
+        num_transfers: u64,
+    ) -> u64 {
+        AmountSegments::new(amount, amount_rule, occurrence_index, num_transfers)
+            .map(|(amount, repeat)| (amount + self.token_fee(amount)).saturating_mul(repeat))
+            .fold(0, u64::saturating_add)
+    }
+}
+
+/// Returns the time bucket a task due at `at` should be processed in: the first bucket at or
+/// after `at` that starts after `now`, since task indexes cannot be created for past buckets.
+pub fn process_at(at: u64, now: u64, time_granularity: u64) -> u64 {
+    let at = max(at, now + 1);
+    (at.saturating_add(time_granularity - 1) / time_granularity).saturating_mul(time_granularity)
+}
+
+/// Returns the tokens a stream accrues at `rate` tokens per second between its last claim and
+/// `until`.
+pub fn stream_accrued(rate: u64, claimed_until: u64, until: u64) -> u64 {
+    rate.saturating_mul(until.saturating_sub(claimed_until))
+}
+
+/// Returns the time a stream paused at `paused_at` and resumed at `now` should be treated as
+/// last claimed, so that the time spent paused does not accrue.
+pub fn stream_resumed_claimed_until(claimed_until: u64, paused_at: u64, now: u64) -> u64 {
+    match claimed_until < paused_at {
+        true => claimed_until + now.saturating_sub(paused_at),
+        false => max(claimed_until, now),
+    }
+}
+
+/// Returns the start of the allowance period containing `now`. Periods of `period` seconds are
+/// counted from `start_at`.
+pub fn allowance_period_start(start_at: u64, period: u64, now: u64) -> u64 {
+    start_at + (now - start_at) / period * period
+}
+
+/// Returns the number of allowance periods between `start_at` and `end_at`, counting a partial
+/// last period as a whole one.
+pub fn allowance_num_periods(start_at: u64, end_at: u64, period: u64) -> u64 {
+    match end_at > start_at {
+        true => (end_at - start_at - 1) / period + 1,
+        false => 0,
+    }
+}
+
+// Civil calendar conversions for the proleptic Gregorian calendar, after Howard Hinnant's
+// `days_from_civil` and `civil_from_days` algorithms. Days are counted from 1970-01-01.
+
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = if year >= 0 { year } else { year - 399 } / 400;
+    let year_of_era = year - era * 400;
+    let month_index = (month as i64 + 9) % 12;
+    let day_of_year = (153 * month_index + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = if days >= 0 { days } else { days - 146_096 } / 146_097;
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+fn is_leap_year(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Returns the weekday of the given day, where 0 is Monday and 6 is Sunday.
+fn weekday_from_days(days: i64) -> u32 {
+    // 1970-01-01 was a Thursday.
+    (days + 3).rem_euclid(7) as u32
+}
+
+fn last_business_day(year: i64, month: u32) -> u32 {
+    let last_day = days_in_month(year, month);
+    match weekday_from_days(days_from_civil(year, month, last_day)) {
+        5 => last_day - 1,
+        6 => last_day - 2,
+        _ => last_day,
+    }
+}
+
+/// Returns the year and month that are the given number of months after the given day.
+fn add_months(days: i64, months: u8) -> (i64, u32) {
+    let (year, month, _) = civil_from_days(days);
+    let month_index = year * 12 + (month as i64 - 1) + months as i64;
+    (
+        month_index.div_euclid(12),
+        (month_index.rem_euclid(12) + 1) as u32,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fees(program_fee_bps: u16, program_fee_min: u64, program_fee_max: u64) -> Fees {
+        Fees {
+            transfer_fee_distributor: 1_000,
+            transfer_fee_program: 500,
+            program_fee_bps,
+            program_fee_min,
+            program_fee_max,
+        }
+    }
+
+    #[test]
+    fn num_transfers_counts_both_ends() {
+        let schedule = Schedule::new(0, 300, 60);
+        assert_eq!(schedule.num_transfers(), 6);
+        assert_eq!(schedule.num_transfers_from(120), 4);
+        assert_eq!(schedule.num_transfers_from(300), 1);
+        assert_eq!(schedule.num_transfers_from(360), 0);
+        assert_eq!(
+            schedule.occurrences().collect::<Vec<_>>(),
+            vec![0, 60, 120, 180, 240, 300]
+        );
+        assert_eq!(Schedule::new(0, 330, 60).num_transfers(), 6);
+        assert_eq!(Schedule::new(60, 60, 0).num_transfers(), 1);
+    }
+
+    #[test]
+    fn extended_end_at_admits_one_more_transfer() {
+        let schedule = Schedule::new(0, 330, 60);
+        let extended = Schedule::new(0, schedule.extended_end_at(120), 60);
+        assert_eq!(extended.end_at, 360);
+        assert_eq!(
+            extended.num_transfers_from(120),
+            schedule.num_transfers_from(120) + 1
+        );
+        assert_eq!(Schedule::new(60, 60, 0).extended_end_at(60), 60);
+    }
+
+    #[test]
+    fn token_fee_is_clamped() {
+        let fees = fees(100, 5, 50);
+        assert_eq!(fees.token_fee(100), 5);
+        assert_eq!(fees.token_fee(2_000), 20);
+        assert_eq!(fees.token_fee(10_000), 50);
+        assert_eq!(
+            Fees {
+                program_fee_max: 0,
+                ..fees
+            }
+            .token_fee(10_000),
+            100
+        );
+        assert_eq!(
+            Fees {
+                program_fee_bps: 0,
+                ..fees
+            }
+            .token_fee(10_000),
+            0
+        );
+        assert_eq!(fees.validate(), Ok(()));
+        assert_eq!(
+            Fees {
+                program_fee_min: 51,
+                ..fees
+            }
+            .validate(),
+            Err(ScheduleError::InvalidFee)
+        );
+    }
+
+    #[test]
+    fn cumulative_token_fee_is_cumulative() {
+        let fees = fees(100, 0, 0);
+        assert_eq!(fees.token_fee(150) * 2, 2);
+        assert_eq!(
+            fees.cumulative_token_fee(0, 150).unwrap()
+                + fees.cumulative_token_fee(150, 150).unwrap(),
+            fees.token_fee(300)
+        );
+
+        // The minimum fee is charged once, on the first claim.
+        let fees = Fees {
+            program_fee_min: 5,
+            ..fees
+        };
+        assert_eq!(fees.cumulative_token_fee(0, 10).unwrap(), 5);
+        assert_eq!(fees.cumulative_token_fee(10, 10).unwrap(), 0);
+        assert_eq!(fees.cumulative_token_fee(20, 0).unwrap(), 0);
+        assert_eq!(
+            fees.cumulative_token_fee(u64::MAX, 1),
+            Err(ScheduleError::Overflow)
+        );
+    }
+
+    #[test]
+    fn lamports_are_checked() {
+        let fees = fees(0, 0, 0);
+        assert_eq!(fees.lamports_per_transfer(), Ok(1_500));
+        assert_eq!(fees.total_lamports(3), Ok(4_500));
+        assert_eq!(fees.total_lamports(u64::MAX), Err(ScheduleError::Overflow));
+        let fees = Fees {
+            transfer_fee_distributor: u64::MAX,
+            ..fees
+        };
+        assert_eq!(fees.lamports_per_transfer(), Err(ScheduleError::Overflow));
+        assert_eq!(fees.total_lamports(0), Err(ScheduleError::Overflow));
+    }
+
+    #[test]
+    fn allowance_num_periods_rounds_up() {
+        assert_eq!(allowance_num_periods(0, 0, 100), 0);
+        assert_eq!(allowance_num_periods(0, 100, 100), 1);
+        assert_eq!(allowance_num_periods(0, 101, 100), 2);
+        assert_eq!(allowance_num_periods(0, 250, 100), 3);
+        assert_eq!(allowance_period_start(0, 100, 250), 200);
+    }
+
+    fn ts(year: i64, month: u32, day: u32) -> u64 {
+        days_from_civil(year, month, day) as u64 * SECONDS_PER_DAY
+    }
+
+    #[test]
+    fn civil_days_round_trip() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+        assert_eq!(days_from_civil(2000, 2, 29), 11_016);
+        assert_eq!(civil_from_days(11_016), (2000, 2, 29));
+        for days in -800_000..800_000 {
+            let (year, month, day) = civil_from_days(days);
+            assert_eq!(days_from_civil(year, month, day), days);
+        }
+    }
+
+    #[test]
+    fn leap_years() {
+        assert_eq!(days_in_month(2024, 2), 29);
+        assert_eq!(days_in_month(2023, 2), 28);
+        assert_eq!(days_in_month(2000, 2), 29);
+        assert_eq!(days_in_month(1900, 2), 28);
+    }
+
+    #[test]
+    fn add_months_rolls_over_years() {
+        assert_eq!(add_months(days_from_civil(2024, 11, 15), 3), (2025, 2));
+        assert_eq!(add_months(days_from_civil(2024, 12, 31), 1), (2025, 1));
+        assert_eq!(add_months(days_from_civil(2024, 1, 31), 12), (2025, 1));
+    }
+
+    #[test]
+    fn last_business_day_skips_weekends() {
+        assert_eq!(last_business_day(2024, 1), 31);
+        assert_eq!(last_business_day(2024, 2), 29);
+        assert_eq!(last_business_day(2024, 3), 29);
+        assert_eq!(last_business_day(2024, 8), 30);
+    }
+
+    #[test]
+    fn monthly_rule_clamps_to_month_end() {
+        let rule = RecurrenceRule::Monthly {
+            interval: 1,
+            day: 31,
+        };
+        assert!(rule.matches(ts(2024, 2, 29)));
+        assert!(!rule.matches(ts(2024, 2, 28)));
+        assert!(rule.matches(ts(2023, 2, 28)));
+        assert_eq!(rule.advance(ts(2024, 1, 31)), Some(ts(2024, 2, 29)));
+        assert_eq!(rule.advance(ts(2023, 1, 31)), Some(ts(2023, 2, 28)));
+
+        // The rule's day is kept after a clamped month.
+        assert_eq!(rule.advance(ts(2024, 2, 29)), Some(ts(2024, 3, 31)));
+    }
+
+    #[test]
+    fn rules_with_intervals() {
+        let rule = RecurrenceRule::Monthly {
+            interval: 3,
+            day: 15,
+        };
+        assert_eq!(rule.advance(ts(2024, 11, 15)), Some(ts(2025, 2, 15)));
+
+        let rule = RecurrenceRule::MonthlyLastBusinessDay { interval: 2 };
+        assert!(rule.matches(ts(2024, 1, 31)));
+        assert_eq!(rule.advance(ts(2024, 1, 31)), Some(ts(2024, 3, 29)));
+
+        // 2024-01-01 was a Monday. Occurrences keep their time of day.
+        let rule = RecurrenceRule::Weekly {
+            interval: 2,
+            weekday: 0,
+        };
+        assert!(rule.matches(ts(2024, 1, 1)));
+        assert!(!rule.matches(ts(2024, 1, 2)));
+        assert_eq!(
+            rule.advance(ts(2024, 1, 1) + 3_600),
+            Some(ts(2024, 1, 15) + 3_600)
+        );
+    }
+
+    #[test]
+    fn rule_occurrences_are_capped() {
+        let rule = Some(RecurrenceRule::Weekly {
+            interval: 1,
+            weekday: 0,
+        });
+        let start_at = ts(2024, 1, 1);
+        let week = 7 * SECONDS_PER_DAY;
+        let schedule = |num_transfers: u64| {
+            Schedule::new(start_at, start_at + (num_transfers - 1) * week, 0).with_rule(rule)
+        };
+        assert_eq!(schedule(MAX_RULE_OCCURRENCES as u64).validate(60), Ok(()));
+        assert_eq!(
+            schedule(MAX_RULE_OCCURRENCES as u64 + 1).validate(60),
+            Err(ScheduleError::InvalidRecurrenceRule)
+        );
+    }
+
+    #[test]
+    fn extended_end_at_with_rule() {
+        let schedule = Schedule::new(ts(2024, 1, 31), ts(2024, 3, 31), 0).with_rule(Some(
+            RecurrenceRule::Monthly {
+                interval: 1,
+                day: 31,
+            },
+        ));
+        assert_eq!(schedule.num_transfers(), 3);
+        assert_eq!(schedule.extended_end_at(ts(2024, 2, 29)), ts(2024, 4, 30));
+    }
+
+    #[test]
+    fn amount_segments_group_equal_amounts() {
+        let segments = |amount_rule, occurrence_index, count| {
+            AmountSegments::new(100, amount_rule, occurrence_index, count).collect::<Vec<_>>()
+        };
+        assert_eq!(segments(None, 3, 4), vec![(100, 4)]);
+        assert_eq!(segments(None, 3, 0), vec![]);
+
+        let ramp = Some(AmountRule::Ramp {
+            step: 10,
+            length: 3,
+        });
+        assert_eq!(
+            segments(ramp, 0, 6),
+            vec![(100, 1), (110, 1), (120, 1), (130, 3)]
+        );
+        assert_eq!(segments(ramp, 2, 3), vec![(120, 1), (130, 2)]);
+    }
+
+    #[test]
+    fn total_amount_matches_amount_at() {
+        let mut amounts = [0; MAX_AMOUNT_STEPS];
+        amounts[..3].copy_from_slice(&[10, 20, 30]);
+        let amount_rules = [
+            None,
+            Some(AmountRule::Ramp {
+                step: 10,
+                length: 3,
+            }),
+            Some(AmountRule::Ramp {
+                step: -60,
+                length: 3,
+            }),
+            Some(AmountRule::Steps { len: 3, amounts }),
+            Some(AmountRule::Escalation {
+                bps: 1_000,
+                every: 2,
+            }),
+        ];
+        for amount_rule in amount_rules {
+            for occurrence_index in 0..6 {
+                for count in 0..8 {
+                    let expected = (occurrence_index..occurrence_index + count)
+                        .map(|index| amount_at(100, amount_rule, index))
+                        .sum::<u64>();
+                    assert_eq!(
+                        total_amount(100, amount_rule, occurrence_index, count),
+                        expected
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn amount_at_follows_the_rule() {
+        let ramp_down = Some(AmountRule::Ramp {
+            step: -60,
+            length: 3,
+        });
+        assert_eq!(amount_at(100, ramp_down, 1), 40);
+        assert_eq!(amount_at(100, ramp_down, 5), 0);
+
+        let mut amounts = [0; MAX_AMOUNT_STEPS];
+        amounts[..2].copy_from_slice(&[10, 20]);
+        let steps = Some(AmountRule::Steps { len: 2, amounts });
+        assert_eq!(amount_at(100, steps, 0), 10);
+        assert_eq!(amount_at(100, steps, 7), 20);
+
+        let escalation = Some(AmountRule::Escalation {
+            bps: 1_000,
+            every: 2,
+        });
+        assert_eq!(amount_at(1_000, escalation, 1), 1_000);
+        assert_eq!(amount_at(1_000, escalation, 2), 1_100);
+        assert_eq!(amount_at(1_000, escalation, 4), 1_210);
+        assert_eq!(total_amount(1_000, escalation, 0, 5), 5_410);
+    }
+
+    #[test]
+    fn lowers_amount_compares_each_occurrence() {
+        let ramp = Some(AmountRule::Ramp {
+            step: 10,
+            length: 3,
+        });
+        assert!(!lowers_amount(100, None, 100, None, 0, 3));
+        assert!(!lowers_amount(110, None, 100, None, 0, 3));
+        assert!(lowers_amount(10, None, 100, None, 0, 3));
+        assert!(!lowers_amount(100, ramp, 100, None, 0, 6));
+        assert!(lowers_amount(100, None, 100, ramp, 0, 6));
+
+        // Moving value to later occurrences at the same total still lowers the earlier ones.
+        assert!(lowers_amount(
+            90,
+            Some(AmountRule::Ramp {
+                step: 20,
+                length: 1,
+            }),
+            100,
+            None,
+            0,
+            2
+        ));
+        assert!(!lowers_amount(130, None, 100, ramp, 3, 3));
+    }
+
+    #[test]
+    fn process_at_rounds_up_to_the_next_future_bucket() {
+        assert_eq!(process_at(120, 100, 60), 120);
+        assert_eq!(process_at(121, 100, 60), 180);
+        assert_eq!(process_at(50, 100, 60), 120);
+        assert_eq!(process_at(120, 120, 60), 180);
+    }
+}
@@ -1,11 +1,32 @@
-use anchor_lang::prelude::*;
+use {crate::schedule::Fees, anchor_lang::prelude::*};
 
 pub const SEED_CONFIG: &[u8] = b"cfg";
 
 #[account]
 pub struct Config {
-    // pub time_granularity: u64,
+    pub admin: Pubkey,
+    pub pending_admin: Option<Pubkey>,
+    /// Size in seconds of the time buckets that tasks are scheduled into.
+    pub time_granularity: u64,
     pub transfer_fee_program: u64,
     pub transfer_fee_distributor: u64,
+    /// Program fee charged in the payment's mint, in basis points of each transfer.
+    pub program_fee_bps: u16,
+    /// Lower bound on the token program fee of a transfer.
+    pub program_fee_min: u64,
+    /// Upper bound on the token program fee of a transfer. Zero means uncapped.
+    pub program_fee_max: u64,
     pub bump: u8,
 }
+
+impl Config {
+    pub fn fees(&self) -> Fees {
+        Fees {
+            transfer_fee_distributor: self.transfer_fee_distributor,
+            transfer_fee_program: self.transfer_fee_program,
+            program_fee_bps: self.program_fee_bps,
+            program_fee_min: self.program_fee_min,
+            program_fee_max: self.program_fee_max,
+        }
+    }
+}
@@ -0,0 +1,38 @@
+use {
+    crate::schedule::{AmountRule, RecurrenceRule},
+    anchor_lang::prelude::*,
+};
+
+pub const SEED_INVOICE: &[u8] = b"inv";
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
+pub enum InvoiceStatus {
+    /// The invoice is waiting for the debtor to accept it.
+    Open,
+    /// The debtor accepted the invoice and its payment was created.
+    Accepted,
+}
+
+#[account]
+pub struct Invoice {
+    pub id: u64,
+    pub memo: String,
+    pub creditor: Pubkey,
+    pub creditor_tokens: Pubkey,
+    pub debtor: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub amount_rule: Option<AmountRule>,
+    pub recurrence_interval: u64,
+    pub recurrence_rule: Option<RecurrenceRule>,
+    pub start_at: u64,
+    pub end_at: u64,
+    pub grace_period: u64,
+    pub retry_interval: u64,
+    pub max_retries: u8,
+    pub max_failures: u8,
+    pub expires_at: u64,
+    pub status: InvoiceStatus,
+    pub payment: Option<Pubkey>,
+    pub bump: u8,
+}
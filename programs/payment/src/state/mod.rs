@@ -1,17 +1,21 @@
 mod authority;
 mod config;
+mod invoice;
 mod namespace;
 mod payment;
 mod role;
 mod task;
-mod task_index;
+mod task_namespace;
 mod treasury;
+mod vault;
 
 pub use authority::*;
 pub use config::*;
+pub use invoice::*;
 pub use namespace::*;
 pub use payment::*;
 pub use role::*;
 pub use task::*;
-pub use task_index::*;
+pub use task_namespace::*;
 pub use treasury::*;
+pub use vault::*;
@@ -1,7 +1,88 @@
-use anchor_lang::prelude::*;
+use {
+    crate::schedule::{
+        amount_at, split_remainder, split_shares, validate_splits, AmountRule, Fees,
+        RecurrenceRule, Schedule, ScheduleError, MAX_SPLITS,
+    },
+    anchor_lang::prelude::*,
+    std::cmp::min,
+};
 
 pub const SEED_PAYMENT: &[u8] = b"pay";
 
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
+pub enum PaymentMode {
+    /// Tokens are transferred in discrete tasks processed by distributors.
+    Scheduled,
+    /// Tokens accrue continuously and are transferred whenever they are claimed.
+    Streaming,
+    /// The creditor charges any amount up to a cap in each period.
+    Allowance,
+    /// Tasks transfer the usage reported by the creditor since the last transfer, priced at the
+    /// payment's unit price and capped at the payment's amount.
+    Metered,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
+pub enum PaymentStatus {
+    /// The payment's tasks are scheduled and will be processed.
+    Active,
+    /// The payment's tasks are skipped until it is resumed.
+    Paused,
+    /// The payment was cancelled and will make no further transfers.
+    Cancelled,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
+pub enum ResumePolicy {
+    /// Transfers skipped while paused are forgone and the schedule continues as planned.
+    Continue,
+    /// Transfers skipped while paused are made up by extending the schedule's end.
+    Shift,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
+pub enum FailureReason {
+    /// No transfer has failed.
+    None,
+    /// The debtor's vault is not the delegate of the debtor's token account.
+    InvalidDelegate,
+    /// The payment's or the vault's delegated amount is less than the transfer amount.
+    InsufficientAllowance,
+    /// The debtor's token balance is less than the transfer amount.
+    InsufficientBalance,
+}
+
+/// The shares of each transfer paid to a payment's split recipients, in basis points. The
+/// creditor receives the rest, including any rounding remainder.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Splits {
+    pub len: u8,
+    pub bps: [u16; MAX_SPLITS],
+}
+
+impl Splits {
+    pub fn validate(&self) -> Result<(), ScheduleError> {
+        match self.len as usize <= MAX_SPLITS {
+            true => validate_splits(self.bps()),
+            false => Err(ScheduleError::InvalidSplits),
+        }
+    }
+
+    /// Returns the amount paid to each split recipient out of a transfer of the given amount.
+    pub fn shares(&self, amount: u64) -> impl Iterator<Item = u64> + '_ {
+        split_shares(self.bps(), amount)
+    }
+
+    /// Returns the amount paid to the creditor out of a transfer of the given amount.
+    pub fn remainder(&self, amount: u64) -> u64 {
+        split_remainder(self.bps(), amount)
+    }
+
+    fn bps(&self) -> &[u16] {
+        &self.bps[..min(self.len as usize, MAX_SPLITS)]
+    }
+}
+
 #[account]
 pub struct Payment {
     pub id: String,
@@ -11,9 +92,72 @@ pub struct Payment {
     pub creditor: Pubkey,
     pub creditor_tokens: Pubkey,
     pub mint: Pubkey,
+    pub mode: PaymentMode,
     pub amount: u64,
+    pub amount_rule: Option<AmountRule>,
     pub recurrence_interval: u64,
+    pub recurrence_rule: Option<RecurrenceRule>,
     pub start_at: u64,
     pub end_at: u64,
+    pub grace_period: u64,
+    pub time_granularity: u64,
+    pub transfer_fee_distributor: u64,
+    pub transfer_fee_program: u64,
+    pub program_fee_bps: u16,
+    pub program_fee_min: u64,
+    pub program_fee_max: u64,
+    pub status: PaymentStatus,
+    pub paused_at: u64,
+    pub resume_policy: ResumePolicy,
+    pub retry_interval: u64,
+    pub max_retries: u8,
+    pub max_failures: u8,
+    pub failure_count: u8,
+    pub last_failure_at: u64,
+    pub last_failure_reason: FailureReason,
+    pub rate: u64,
+    pub claimed_until: u64,
+    pub claimed_amount: u64,
+    pub period_started_at: u64,
+    pub period_spent: u64,
+    pub unit_price: u64,
+    pub usage: u64,
+    pub delegated_amount: u64,
+    pub splits: Option<Splits>,
+    pub split_tokens: [Pubkey; MAX_SPLITS],
     pub bump: u8,
 }
+
+impl Payment {
+    pub fn fees(&self) -> Fees {
+        Fees {
+            transfer_fee_distributor: self.transfer_fee_distributor,
+            transfer_fee_program: self.transfer_fee_program,
+            program_fee_bps: self.program_fee_bps,
+            program_fee_min: self.program_fee_min,
+            program_fee_max: self.program_fee_max,
+        }
+    }
+
+    /// Returns the amount due on the given occurrence.
+    pub fn amount_at(&self, occurrence_index: u64) -> u64 {
+        amount_at(self.amount, self.amount_rule, occurrence_index)
+    }
+
+    /// Returns the amount to transfer on the given occurrence. Metered payments transfer their
+    /// reported usage, up to the occurrence's amount.
+    pub fn amount_due(&self, occurrence_index: u64) -> u64 {
+        match self.mode {
+            PaymentMode::Metered => min(
+                self.usage.saturating_mul(self.unit_price),
+                self.amount_at(occurrence_index),
+            ),
+            _ => self.amount_at(occurrence_index),
+        }
+    }
+
+    pub fn schedule(&self) -> Schedule {
+        Schedule::new(self.start_at, self.end_at, self.recurrence_interval)
+            .with_rule(self.recurrence_rule)
+    }
+}
@@ -5,3 +5,12 @@ pub enum Role {
     Creditor,
     Debtor,
 }
+
+impl Role {
+    pub fn seed(&self) -> &[u8] {
+        match self {
+            Role::Creditor => b"cr",
+            Role::Debtor => b"db",
+        }
+    }
+}
@@ -2,16 +2,35 @@ use anchor_lang::prelude::*;
 
 pub const SEED_TASK: &[u8] = b"tsk";
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
 pub enum TaskStatus {
+    /// The task is waiting to be processed.
     Pending,
-    MarkedForRepitition,
+    /// The task was processed and its transfer succeeded.
+    Done,
+    /// The task was processed but its transfer could not be made.
+    Failed,
+    /// The task was processed without attempting a transfer.
+    Skipped,
+    /// The task's payment was cancelled before the task was processed. Tasks passed to
+    /// `cancel_payment` are closed instead; any others are marked cancelled when processed.
+    Cancelled,
+    /// The task was not processed within its payment's grace period.
+    Expired,
 }
 
 #[account]
 pub struct Task {
     pub id: String,
     pub payment: Pubkey,
+    /// The account that paid the task's rent, which is refunded to it if the task is closed.
+    pub payer: Pubkey,
+    pub process_at: u64,
+    pub occurrence_at: u64,
+    pub occurrence_index: u64,
+    pub attempt: u8,
     pub status: TaskStatus,
+    pub processed_at: u64,
+    pub processed_slot: u64,
     pub bump: u8,
 }
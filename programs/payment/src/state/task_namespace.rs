@@ -0,0 +1,9 @@
+use anchor_lang::prelude::*;
+
+pub const SEED_TASK_NAMESPACE: &[u8] = b"tsk_ns";
+
+#[account]
+pub struct TaskNamespace {
+    pub process_at: u64,
+    pub bump: u8,
+}
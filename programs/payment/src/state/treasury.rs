@@ -4,5 +4,9 @@ pub const SEED_TREASURY: &[u8] = b"trs";
 
 #[account]
 pub struct Treasury {
+    pub total_program_fees_collected: u64,
+    pub total_distributor_fees_paid: u64,
+    pub total_transfers_processed: u64,
+    pub total_withdrawn: u64,
     pub bump: u8,
 }
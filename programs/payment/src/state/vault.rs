@@ -0,0 +1,14 @@
+use anchor_lang::prelude::*;
+
+pub const SEED_VAULT: &[u8] = b"vlt";
+
+/// The single delegate of a debtor's token account. Each payment drawing from the token account
+/// holds a share of the vault's delegated amount, so that payments do not overwrite each other's
+/// delegation.
+#[account]
+pub struct Vault {
+    pub debtor: Pubkey,
+    pub debtor_tokens: Pubkey,
+    pub delegated_amount: u64,
+    pub bump: u8,
+}
@@ -9,7 +9,13 @@ import {
   SystemProgram,
   SYSVAR_CLOCK_PUBKEY,
 } from "@solana/web3.js";
-import { newSigner, signAndSubmit } from "../../../utils";
+import {
+  dateToSeconds,
+  newSigner,
+  signAndSubmit,
+  sleepUntil,
+  validateAccountDoesNotExist,
+} from "../../../utils";
 import * as client from "../client";
 import { IndexProgram } from "../../../target/types/index_program";
 
@@ -27,10 +33,14 @@ describe("Payment Program", () => {
   anchor.setProvider(provider);
 
   // Shared data
+  let admin: Keypair;
   let creditor: Keypair;
   let debtor: Keypair;
   let worker: Keypair;
   let taskIndexProcessAt: number;
+  let creditorTokens: PublicKey;
+  let debtorTokens: PublicKey;
+  let treasuryTokens: PublicKey;
 
   before(async () => {
     await client.loadPDAs(client.program.programId);
@@ -40,20 +50,20 @@ describe("Payment Program", () => {
   });
 
   it("initializes the program", async () => {
-    // Generate test data.
-    const signer = await newSigner(provider.connection);
+    // Generate test data. Only the program's upgrade authority can initialize it.
+    admin = (provider.wallet as anchor.Wallet).payer;
     const transferFeeDistributor = 1000;
     const transferFeeProgram = 1000;
 
     // Create instructions.
     const ix = client.initializeProgram({
-      signer: signer.publicKey,
+      signer: admin.publicKey,
       transferFeeDistributor,
       transferFeeProgram,
     });
 
     // Sign and submit transaction.
-    await signAndSubmit(client.connection(), [ix], signer);
+    await signAndSubmit(client.connection(), [ix], admin);
 
     // Validate config account data.
     const configData = await client.program.account.config.fetch(
@@ -63,15 +73,56 @@ describe("Payment Program", () => {
       configData.transferFeeDistributor.toNumber() === transferFeeDistributor
     );
     assert.ok(configData.transferFeeProgram.toNumber() === transferFeeProgram);
+    assert.ok(configData.admin.equals(admin.publicKey));
+    assert.ok(configData.pendingAdmin === null);
+    assert.ok(configData.timeGranularity.toNumber() === 60);
     assert.ok(configData.bump === client.configPDA.bump);
 
     // Validate treasury account data.
     const treasuryData = await client.program.account.treasury.fetch(
       client.treasuryPDA.address
     );
+    assert.ok(treasuryData.totalProgramFeesCollected.toNumber() === 0);
+    assert.ok(treasuryData.totalTransfersProcessed.toNumber() === 0);
     assert.ok(treasuryData.bump === client.treasuryPDA.bump);
   });
 
+  it("updates the config", async () => {
+    // Generate test data.
+    const transferFeeDistributor = 2000;
+    const transferFeeProgram = 500;
+    const programFeeBps = 25;
+    const programFeeMin = 0;
+    const programFeeMax = 0;
+    const timeGranularity = 60;
+
+    // Create instructions.
+    const ix = client.updateConfig({
+      admin: admin.publicKey,
+      transferFeeDistributor,
+      transferFeeProgram,
+      programFeeBps,
+      programFeeMin,
+      programFeeMax,
+      timeGranularity,
+    });
+
+    // Sign and submit transaction.
+    await signAndSubmit(client.connection(), [ix], admin);
+
+    // Validate config account data.
+    const configData = await client.program.account.config.fetch(
+      client.configPDA.address
+    );
+    assert.ok(
+      configData.transferFeeDistributor.toNumber() === transferFeeDistributor
+    );
+    assert.ok(configData.transferFeeProgram.toNumber() === transferFeeProgram);
+    assert.ok(configData.programFeeBps === programFeeBps);
+    assert.ok(configData.timeGranularity.toNumber() === timeGranularity);
+    assert.ok(configData.admin.equals(admin.publicKey));
+  });
+
   it("creates a creditor payment index", async () => {
     // Generate test data
     const signer = await newSigner(provider.connection);
@@ -81,6 +132,7 @@ describe("Payment Program", () => {
       indexProgram: indexProgram.programId,
       party: debtor.publicKey,
       payer: signer.publicKey,
+      role: "creditor",
     });
 
     // Sign and submit transaction.
@@ -107,6 +159,308 @@ describe("Payment Program", () => {
     assert.ok(namespaceData.bump === namespacePDA.bump);
   });
 
+  it("creates the payment indexes of a payment's parties", async () => {
+    // Generate instructions.
+    const creditorIndex = await client.createPaymentIndex({
+      indexProgram: indexProgram.programId,
+      party: creditor.publicKey,
+      payer: creditor.publicKey,
+      role: "creditor",
+    });
+    const debtorIndex = await client.createPaymentIndex({
+      indexProgram: indexProgram.programId,
+      party: debtor.publicKey,
+      payer: debtor.publicKey,
+      role: "debtor",
+    });
+
+    // Sign and submit transactions.
+    await signAndSubmit(client.connection(), [creditorIndex.ix], creditor);
+    await signAndSubmit(client.connection(), [debtorIndex.ix], debtor);
+
+    // Validate index account data.
+    for (const { indexPDA } of [creditorIndex, debtorIndex]) {
+      const indexData = await indexProgram.account.index.fetch(
+        indexPDA.address
+      );
+      assert.ok(indexData.count.toNumber() === 0);
+    }
+  });
+
+  it("creates a vault", async () => {
+    // Generate token accounts. The treasury collects token program fees in its own account.
+    debtorTokens = await Token.createWrappedNativeAccount(
+      provider.connection,
+      TOKEN_PROGRAM_ID,
+      debtor.publicKey,
+      debtor,
+      LAMPORTS_PER_SOL / 2
+    );
+    creditorTokens = await Token.createWrappedNativeAccount(
+      provider.connection,
+      TOKEN_PROGRAM_ID,
+      creditor.publicKey,
+      creditor,
+      0
+    );
+    treasuryTokens = await Token.createWrappedNativeAccount(
+      provider.connection,
+      TOKEN_PROGRAM_ID,
+      client.treasuryPDA.address,
+      worker,
+      0
+    );
+
+    // Generate instruction.
+    const { ix, vaultPDA } = await client.createVault({
+      debtor: debtor.publicKey,
+      debtorTokens,
+    });
+
+    // Sign and submit transaction.
+    await signAndSubmit(client.connection(), [ix], debtor);
+
+    // Validate vault account data.
+    const vaultData = await client.program.account.vault.fetch(
+      vaultPDA.address
+    );
+    assert.ok(vaultData.debtor.equals(debtor.publicKey));
+    assert.ok(vaultData.debtorTokens.equals(debtorTokens));
+    assert.ok(vaultData.delegatedAmount.toNumber() === 0);
+    assert.ok(vaultData.bump === vaultPDA.bump);
+  });
+
+  it("processes a task and rejects processing it again", async () => {
+    // Generate test data.
+    const amount = LAMPORTS_PER_SOL / 100;
+    const processAt = await createTaskIndex(nextMinute());
+
+    // Create a one-time payment.
+    const { ix, paymentPDA, taskPDA } = await client.createPayment({
+      ...paymentProps(),
+      amount,
+      startAt: processAt,
+      endAt: processAt,
+    });
+    await signAndSubmit(client.connection(), [ix], debtor);
+
+    // Process the task once it is due.
+    await sleepUntil(new Date((processAt + 2) * 1000));
+    const processTask = await client.processTask({
+      task: taskPDA.address,
+      signer: worker.publicKey,
+      treasuryTokens,
+    });
+    await signAndSubmit(client.connection(), [processTask.ix], worker);
+
+    // Validate the task is done and the payment's reservation is released.
+    const taskData = await client.program.account.task.fetch(taskPDA.address);
+    assert.ok(Object.keys(taskData.status)[0] === "done");
+    assert.ok(taskData.processedAt.toNumber() >= processAt);
+    assert.ok(taskData.processedSlot.toNumber() > 0);
+    const paymentData = await client.program.account.payment.fetch(
+      paymentPDA.address
+    );
+    assert.ok(paymentData.delegatedAmount.toNumber() === 0);
+    assert.ok((await tokenBalance(creditorTokens)) === amount);
+
+    // Processing the task again is rejected, and transfers nothing.
+    const reprocessTask = await client.processTask({
+      task: taskPDA.address,
+      signer: worker.publicKey,
+      treasuryTokens,
+    });
+    await assert.rejects(
+      signAndSubmit(client.connection(), [reprocessTask.ix], worker)
+    );
+    assert.ok((await tokenBalance(creditorTokens)) === amount);
+  });
+
+  it("retries a failed transfer and gives up after its retries", async () => {
+    // Generate test data. The debtor's tokens cannot cover the transfer.
+    const amount = LAMPORTS_PER_SOL * 10;
+    const processAt = await createTaskIndex(nextMinute());
+
+    // Processed within a minute of its due time, a failed task is retried in the bucket one
+    // retry interval after the current one.
+    const retryAt = await createTaskIndex(processAt + 2 * ONE_MINUTE);
+
+    // Create a one-time payment with one retry.
+    const { ix, paymentPDA, taskPDA } = await client.createPayment({
+      ...paymentProps(),
+      amount,
+      startAt: processAt,
+      endAt: processAt,
+      retryInterval: ONE_MINUTE,
+      maxRetries: 1,
+    });
+    await signAndSubmit(client.connection(), [ix], debtor);
+
+    // Process the task once it is due.
+    await sleepUntil(new Date((processAt + 2) * 1000));
+    const processTask = await client.processTask({
+      task: taskPDA.address,
+      signer: worker.publicKey,
+      treasuryTokens,
+      nextProcessAt: retryAt,
+    });
+    await signAndSubmit(client.connection(), [processTask.ix], worker);
+
+    // Validate the failure is recorded and a retry of the same occurrence is scheduled.
+    const taskData = await client.program.account.task.fetch(taskPDA.address);
+    assert.ok(Object.keys(taskData.status)[0] === "failed");
+    let paymentData = await client.program.account.payment.fetch(
+      paymentPDA.address
+    );
+    assert.ok(paymentData.failureCount === 1);
+    assert.ok(
+      Object.keys(paymentData.lastFailureReason)[0] === "insufficientBalance"
+    );
+    assert.ok(paymentData.delegatedAmount.toNumber() > amount);
+    const retryTaskData = await client.program.account.task.fetch(
+      processTask.nextTaskPDA.address
+    );
+    assert.ok(Object.keys(retryTaskData.status)[0] === "pending");
+    assert.ok(retryTaskData.processAt.toNumber() === retryAt);
+    assert.ok(retryTaskData.occurrenceAt.toNumber() === processAt);
+    assert.ok(retryTaskData.occurrenceIndex.toNumber() === 0);
+    assert.ok(retryTaskData.attempt === 1);
+    assert.ok(retryTaskData.payer.equals(worker.publicKey));
+
+    // Processing the failed task again is rejected.
+    const reprocessTask = await client.processTask({
+      task: taskPDA.address,
+      signer: worker.publicKey,
+      treasuryTokens,
+    });
+    await assert.rejects(
+      signAndSubmit(client.connection(), [reprocessTask.ix], worker)
+    );
+
+    // The retry fails too. The payment gives up on the transfer and releases its tokens.
+    await sleepUntil(new Date((retryAt + 2) * 1000));
+    const processRetryTask = await client.processTask({
+      task: processTask.nextTaskPDA.address,
+      signer: worker.publicKey,
+      treasuryTokens,
+    });
+    await signAndSubmit(client.connection(), [processRetryTask.ix], worker);
+    const processedRetryTaskData = await client.program.account.task.fetch(
+      processTask.nextTaskPDA.address
+    );
+    assert.ok(Object.keys(processedRetryTaskData.status)[0] === "failed");
+    paymentData = await client.program.account.payment.fetch(
+      paymentPDA.address
+    );
+    assert.ok(paymentData.failureCount === 2);
+    assert.ok(paymentData.delegatedAmount.toNumber() === 0);
+  });
+
+  it("releases a cancelled payment's reservation in the vault", async () => {
+    // Generate test data.
+    const amount = LAMPORTS_PER_SOL / 100;
+    const processAt = await createTaskIndex(nextMinute() + ONE_MINUTE);
+    const vaultPDA = await client.findVaultPDA(debtor.publicKey, debtorTokens);
+    const vaultDataBefore = await client.program.account.vault.fetch(
+      vaultPDA.address
+    );
+
+    // Create a one-time payment.
+    const { ix, paymentPDA, taskPDA } = await client.createPayment({
+      ...paymentProps(),
+      amount,
+      startAt: processAt,
+      endAt: processAt,
+    });
+    await signAndSubmit(client.connection(), [ix], debtor);
+
+    // Validate the payment's tokens, including the token program fee, are reserved.
+    let paymentData = await client.program.account.payment.fetch(
+      paymentPDA.address
+    );
+    let vaultData = await client.program.account.vault.fetch(vaultPDA.address);
+    assert.ok(paymentData.delegatedAmount.toNumber() > amount);
+    assert.ok(
+      vaultData.delegatedAmount.toNumber() ===
+        vaultDataBefore.delegatedAmount.toNumber() +
+          paymentData.delegatedAmount.toNumber()
+    );
+
+    // Cancel the payment, closing its pending task.
+    const cancelPayment = await client.cancelPayment({
+      payment: paymentPDA.address,
+      signer: debtor.publicKey,
+      tasks: [taskPDA.address],
+    });
+    await signAndSubmit(client.connection(), [cancelPayment], debtor);
+
+    // Validate the reservation is released and the task is closed.
+    paymentData = await client.program.account.payment.fetch(
+      paymentPDA.address
+    );
+    vaultData = await client.program.account.vault.fetch(vaultPDA.address);
+    assert.ok(Object.keys(paymentData.status)[0] === "cancelled");
+    assert.ok(paymentData.delegatedAmount.toNumber() === 0);
+    assert.ok(
+      vaultData.delegatedAmount.toNumber() ===
+        vaultDataBefore.delegatedAmount.toNumber()
+    );
+    await validateAccountDoesNotExist(client.program, taskPDA.address);
+
+    // No other payment reserves the debtor's tokens, so the vault's delegation is revoked.
+    const debtorTokensData = await new Token(
+      provider.connection,
+      WSOL_MINT,
+      TOKEN_PROGRAM_ID,
+      debtor
+    ).getAccountInfo(debtorTokens);
+    assert.ok(vaultData.delegatedAmount.toNumber() === 0);
+    assert.ok(debtorTokensData.delegate === null);
+  });
+
+  // Returns the start of the next minute, leaving time to create its task index.
+  function nextMinute(): number {
+    const now = dateToSeconds(new Date());
+    return (Math.floor((now + 5) / ONE_MINUTE) + 1) * ONE_MINUTE;
+  }
+
+  // Creates the task index for the given process time, and returns the process time.
+  async function createTaskIndex(processAt: number): Promise<number> {
+    const { ix } = await client.createTaskIndex({
+      indexProgram: indexProgram.programId,
+      processAt,
+      signer: worker.publicKey,
+    });
+    await signAndSubmit(client.connection(), [ix], worker);
+    return processAt;
+  }
+
+  // Returns the props of a one-time payment from the debtor to the creditor, without retries.
+  function paymentProps(): client.CreatePaymentProps {
+    return {
+      creditor: creditor.publicKey,
+      creditorTokens,
+      debtor: debtor.publicKey,
+      debtorTokens,
+      mint: WSOL_MINT,
+      memo: "Test",
+      amount: 0,
+      recurrenceInterval: 0,
+      startAt: 0,
+      endAt: 0,
+      gracePeriod: 0,
+      retryInterval: 0,
+      maxRetries: 0,
+      maxFailures: 0,
+    };
+  }
+
+  // Returns the balance of a token account.
+  async function tokenBalance(tokens: PublicKey): Promise<number> {
+    const balance = await provider.connection.getTokenAccountBalance(tokens);
+    return Number(balance.value.amount);
+  }
+
   // it("creates a debtor payment index", async () => {
   //   // Generate test data.
   //   const [paymentIndexAddress, paymentIndexBump] =